@@ -14,7 +14,7 @@ endif
 set iskeyword=a-z,A-Z
 
 " Language keywords
-syntax keyword raoulKeywords AND OR NOT bool float int string void func if else print while for to global true false return
+syntax keyword raoulKeywords AND OR NOT IMPLIES IFF bool float int string void func struct if else print while for to match global true false return assert error
 
 " Comments
 syntax region raoulCommentLine start="//" end="$"
@@ -1,14 +1,68 @@
 use std::{cmp::Ordering, collections::HashMap, fmt};
 
+use serde::{Deserialize, Serialize};
+
 use crate::{
     dir_func::{variable::Dimensions, variable_value::VariableValue},
     enums::Types,
     vm::VMResult,
 };
 
-const THRESHOLD: usize = 250;
+pub const THRESHOLD: usize = 2_500;
 const COUNTER_SIZE: usize = 4;
 pub const TOTAL_SIZE: usize = THRESHOLD * COUNTER_SIZE;
+/// Address of the dataframe variables, whose value is kept by the VM instead
+/// of the memory. Neither the managers nor the pointers ever reach it
+pub const DATAFRAME_ADDRESS: usize = usize::MAX;
+
+/// Memory segment an address belongs to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scope {
+    Global,
+    Local,
+    Temp,
+    Constant,
+    Pointer,
+}
+
+impl From<usize> for Scope {
+    fn from(address: usize) -> Self {
+        match address / TOTAL_SIZE {
+            0 => Scope::Global,
+            1 => Scope::Local,
+            2 => Scope::Temp,
+            3 => Scope::Constant,
+            _ => Scope::Pointer,
+        }
+    }
+}
+
+impl Scope {
+    /// First address of the segment
+    pub fn start(self) -> usize {
+        let index = match self {
+            Scope::Global => 0,
+            Scope::Local => 1,
+            Scope::Temp => 2,
+            Scope::Constant => 3,
+            Scope::Pointer => 4,
+        };
+        index * TOTAL_SIZE
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Scope::Global => "global",
+            Scope::Local => "local",
+            Scope::Temp => "temp",
+            Scope::Constant => "constant",
+            Scope::Pointer => "pointer",
+        };
+        write!(f, "{name}")
+    }
+}
 
 pub trait Address {
     fn is_temp_address(&self) -> bool;
@@ -17,7 +71,7 @@ pub trait Address {
 
 impl Address for usize {
     fn is_temp_address(&self) -> bool {
-        TOTAL_SIZE * 2 < *self && *self < TOTAL_SIZE * 3
+        TOTAL_SIZE * 2 <= *self && *self < TOTAL_SIZE * 3
     }
 
     fn is_pointer_address(&self) -> bool {
@@ -25,7 +79,7 @@ impl Address for usize {
     }
 }
 
-impl Address for Option<usize> {
+impl<T: Address> Address for Option<T> {
     fn is_temp_address(&self) -> bool {
         match self {
             Some(address) => address.is_temp_address(),
@@ -43,6 +97,18 @@ impl Address for Option<usize> {
 
 type AddressCounter = HashMap<Types, usize>;
 
+/// Type of the values an address holds. Every segment is split by type the
+/// same way, so it doesn't depend on the scope of the address
+pub fn address_type(address: usize) -> Types {
+    match address % TOTAL_SIZE / THRESHOLD {
+        0 => Types::Int,
+        1 => Types::Float,
+        2 => Types::String,
+        3 => Types::Bool,
+        _ => unreachable!(),
+    }
+}
+
 fn get_type_base(data_type: Types) -> usize {
     match data_type {
         Types::Int => 0,
@@ -53,7 +119,8 @@ fn get_type_base(data_type: Types) -> usize {
     }
 }
 
-fn get_amount(dimensions: Dimensions) -> usize {
+/// Amount of slots a variable with the given dimensions takes
+pub fn get_amount(dimensions: Dimensions) -> usize {
     let dim_1 = dimensions.0.unwrap_or(0);
     let dim_2 = dimensions.1.unwrap_or(1);
     match dim_1 * dim_2 {
@@ -69,7 +136,7 @@ pub trait GenericAddressManager {
     fn get_base(&self) -> usize;
 }
 
-#[derive(PartialEq, Clone)]
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
 #[allow(clippy::module_name_repetitions)]
 pub struct AddressManager {
     base: usize,
@@ -96,7 +163,7 @@ impl GenericAddressManager for AddressManager {
     }
     fn get_address(&mut self, data_type: Types, dimensions: Dimensions) -> Option<usize> {
         if data_type == Types::Dataframe {
-            return Some(10_000);
+            return Some(DATAFRAME_ADDRESS);
         }
         let type_counter = self
             .counter
@@ -141,7 +208,7 @@ impl fmt::Debug for AddressManager {
     }
 }
 
-#[derive(PartialEq, Clone)]
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
 pub struct TempAddressManager {
     address_manager: AddressManager,
     released: HashMap<Types, Vec<usize>>,
@@ -162,29 +229,17 @@ impl TempAddressManager {
         }
     }
 
-    fn address_type(&self, address: usize) -> Types {
-        let contextless_address = address - self.address_manager.base;
-        let type_determinant = contextless_address / THRESHOLD;
-        match type_determinant {
-            0 => Types::Int,
-            1 => Types::Float,
-            2 => Types::String,
-            3 => Types::Bool,
-            _ => unreachable!(
-                "{:?}, {:?}, {:?}",
-                address, contextless_address, type_determinant
-            ),
-        }
-    }
-
     #[inline]
     fn type_released_addresses(&mut self, data_type: &Types) -> &mut Vec<usize> {
         self.released.get_mut(data_type).unwrap()
     }
 
     pub fn release_address(&mut self, address: usize) {
-        let data_type = self.address_type(address);
-        self.type_released_addresses(&data_type).push(address);
+        let data_type = address_type(address);
+        let released = self.type_released_addresses(&data_type);
+        if !released.contains(&address) {
+            released.push(address);
+        }
     }
 }
 
@@ -221,25 +276,12 @@ impl fmt::Debug for TempAddressManager {
     }
 }
 
-#[derive(PartialEq, Clone)]
+#[derive(PartialEq, Clone, Serialize, Deserialize)]
 pub struct ConstantMemory {
     base: usize,
     memory: HashMap<Types, Vec<VariableValue>>,
 }
 
-fn get_address_info(address: usize, base: usize) -> (usize, usize, Types) {
-    let contextless_address = address - base;
-    let type_determinant = contextless_address / THRESHOLD;
-    let address_type = match type_determinant {
-        0 => Types::Int,
-        1 => Types::Float,
-        2 => Types::String,
-        3 => Types::Bool,
-        _ => unreachable!(),
-    };
-    (contextless_address, type_determinant, address_type)
-}
-
 impl ConstantMemory {
     pub fn new() -> Self {
         let memory = HashMap::from([
@@ -280,23 +322,27 @@ impl ConstantMemory {
     }
 
     pub fn get(&self, address: usize) -> &VariableValue {
-        let (contextless_address, type_determinant, address_type) =
-            get_address_info(address, self.base);
         self.memory
-            .get(&address_type)
+            .get(&address_type(address))
             .unwrap()
-            .get(contextless_address - type_determinant * THRESHOLD)
+            .get((address - self.base) % THRESHOLD)
             .unwrap()
     }
 }
 
+impl Default for ConstantMemory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl fmt::Debug for ConstantMemory {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "ConstantMemory({:?})", self.memory)
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct Memory {
     base: usize,
     int_pointer: usize,
@@ -327,8 +373,8 @@ impl Memory {
     }
 
     fn get_index(&self, address: usize) -> (usize, Types) {
-        let (contextless_address, _, address_type) = get_address_info(address, self.base);
-        let type_index = contextless_address % THRESHOLD;
+        let address_type = address_type(address);
+        let type_index = (address - self.base) % THRESHOLD;
         let pointer = match address_type {
             Types::Int => self.int_pointer,
             Types::Float => self.float_pointer,
@@ -350,9 +396,45 @@ impl Memory {
         *self.space.get_mut(index).unwrap() = Some(value);
         Ok(())
     }
+
+    /// Pushes `suffix` into the string stored at `address` in place
+    pub fn append(&mut self, address: usize, suffix: &str) -> VMResult<()> {
+        let index = self.get_index(address).0;
+        match self.space.get_mut(index).unwrap() {
+            Some(VariableValue::String(value)) => {
+                value.push_str(suffix);
+                Ok(())
+            }
+            _ => Err("Found initialized value".to_owned()),
+        }
+    }
+}
+
+/// Shows only the initialized slots, each one with its scope and address
+impl fmt::Debug for Memory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let slots = [
+            (Types::Int, self.int_pointer..self.float_pointer),
+            (Types::Float, self.float_pointer..self.string_pointer),
+            (Types::String, self.string_pointer..self.bool_pointer),
+            (Types::Bool, self.bool_pointer..self.space.len()),
+        ];
+        let scope = Scope::from(self.base);
+        write!(f, "Memory ")?;
+        let mut map = f.debug_map();
+        for (data_type, range) in slots {
+            let type_base = self.base + get_type_base(data_type);
+            for (offset, value) in self.space[range].iter().enumerate() {
+                if let Some(value) = value {
+                    map.entry(&format_args!("{scope} {}", type_base + offset), value);
+                }
+            }
+        }
+        map.finish()
+    }
 }
 
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct PointerMemory {
     counter: usize,
     pointers: HashMap<usize, usize>,
@@ -381,5 +463,11 @@ impl PointerMemory {
     }
 }
 
+impl Default for PointerMemory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests;
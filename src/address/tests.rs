@@ -10,10 +10,104 @@ fn valid_get_address() {
 #[test]
 fn invalid_get_address() {
     let mut address_manager = AddressManager::new(0);
-    for i in 0..250 {
+    for i in 0..THRESHOLD {
         let address = address_manager.get_address(Types::Int, (None, None));
         assert_eq!(address, Some(i));
     }
     let address = address_manager.get_address(Types::Int, (None, None));
     assert_eq!(address, None);
 }
+
+#[test]
+fn scope_of_address() {
+    assert_eq!(Scope::from(0), Scope::Global);
+    assert_eq!(Scope::from(TOTAL_SIZE + 5), Scope::Local);
+    assert_eq!(Scope::from(TOTAL_SIZE * 2), Scope::Temp);
+    assert_eq!(Scope::from(TOTAL_SIZE * 3 + THRESHOLD), Scope::Constant);
+    assert_eq!(Scope::from(TOTAL_SIZE * 7), Scope::Pointer);
+}
+
+#[test]
+fn memory_debug_shows_scope() {
+    let mut address_manager = AddressManager::new(TOTAL_SIZE);
+    let int = address_manager
+        .get_address(Types::Int, (None, None))
+        .unwrap();
+    address_manager.get_address(Types::Int, (None, None));
+    let float = address_manager
+        .get_address(Types::Float, (None, None))
+        .unwrap();
+    let mut memory = Memory::new(&address_manager);
+    memory.write(int, &VariableValue::Integer(3)).unwrap();
+    memory.write(float, &VariableValue::Float(1.5)).unwrap();
+    assert_eq!(
+        format!("{memory:?}"),
+        "Memory {local 10000: 3, local 12500: 1.5}"
+    );
+}
+
+#[test]
+fn full_scopes_dont_collide() {
+    let bases = [0, TOTAL_SIZE, TOTAL_SIZE * 2];
+    let types = [Types::Int, Types::Float, Types::String, Types::Bool];
+    let mut seen = std::collections::HashSet::new();
+    for base in bases {
+        let mut address_manager = AddressManager::new(base);
+        for data_type in types {
+            for _ in 0..THRESHOLD {
+                let address = address_manager.get_address(data_type, (None, None));
+                let address = address.unwrap();
+                assert_eq!(Scope::from(address), Scope::from(base));
+                assert!(seen.insert(address), "{address} was given twice");
+            }
+            assert_eq!(address_manager.get_address(data_type, (None, None)), None);
+        }
+        let mut memory = Memory::new(&address_manager);
+        let last_int = base + THRESHOLD - 1;
+        let first_float = base + THRESHOLD;
+        memory.write(last_int, &VariableValue::Integer(1)).unwrap();
+        memory
+            .write(first_float, &VariableValue::Float(2.0))
+            .unwrap();
+        assert_eq!(memory.get(last_int), &Some(VariableValue::Integer(1)));
+        assert_eq!(memory.get(first_float), &Some(VariableValue::Float(2.0)));
+    }
+    let mut constants = ConstantMemory::new();
+    let (address, _) = constants.add(VariableValue::Bool(true)).unwrap();
+    assert_eq!(Scope::from(address), Scope::Constant);
+    assert!(!seen.contains(&address));
+}
+
+#[test]
+fn dataframe_address_is_in_no_scope() {
+    let types = [Types::Int, Types::Float, Types::String, Types::Bool];
+    for base in [0, TOTAL_SIZE, TOTAL_SIZE * 2] {
+        let mut address_manager = AddressManager::new(base);
+        let dataframe = address_manager.get_address(Types::Dataframe, (None, None));
+        assert_eq!(dataframe, Some(DATAFRAME_ADDRESS));
+        for data_type in types {
+            for _ in 0..THRESHOLD {
+                let address = address_manager.get_address(data_type, (None, None));
+                assert_ne!(address, dataframe);
+            }
+        }
+    }
+    let mut constants = ConstantMemory::new();
+    for i in 0..THRESHOLD as i64 {
+        let (address, _) = constants.add(VariableValue::Integer(i)).unwrap();
+        assert_ne!(address, DATAFRAME_ADDRESS);
+    }
+    let mut pointers = PointerMemory::new();
+    for _ in 0..TOTAL_SIZE {
+        assert_ne!(pointers.get_pointer(), DATAFRAME_ADDRESS);
+    }
+}
+
+#[test]
+fn fits_a_large_array() {
+    let mut address_manager = AddressManager::new(0);
+    let address = address_manager.get_address(Types::Int, (Some(30), Some(10)));
+    assert_eq!(address, Some(0));
+    let next = address_manager.get_address(Types::Int, (None, None));
+    assert_eq!(next, Some(300));
+}
@@ -1,14 +1,66 @@
-use clap::{Arg, ArgMatches, Command};
+use clap::{Arg, ArgGroup, ArgMatches, Command};
+
+/// Whether debugging prints are enabled. The `-d` flag always enables them,
+/// without it `RAOUL_DEBUG` is used, being enabled for any value other than
+/// empty, `0` or `false`
+pub fn is_debug(matches: &ArgMatches) -> bool {
+    if matches.is_present("debug") {
+        return true;
+    }
+    std::env::var("RAOUL_DEBUG")
+        .map(|value| !matches!(value.trim(), "" | "0" | "false"))
+        .unwrap_or(false)
+}
 
 pub fn parse_arguments() -> ArgMatches {
     Command::new("raoul")
-        .version("1.0")
+        .version(raoul::VERSION)
         .author("ricglz")
         .about("My cool programming language")
+        .subcommand_negates_reqs(true)
+        .subcommand(
+            Command::new("fmt")
+                .about("Prints the program formatted, dropping its comments")
+                .arg(
+                    Arg::new("file")
+                        .value_name("FILE")
+                        .help("Sets the file to format")
+                        .required(true),
+                ),
+        )
         .arg(
             Arg::new("file")
                 .value_name("FILE")
                 .help("Sets a file to parse")
+                .required(false),
+        )
+        .arg(
+            Arg::new("eval")
+                .long("eval")
+                .value_name("SOURCE")
+                .help("Runs the given source code instead of a file")
+                .takes_value(true)
+                .required(false),
+        )
+        .arg(
+            Arg::new("batch")
+                .long("batch")
+                .value_name("BATCH")
+                .help("Runs every program read from stdin, separated by `---` lines, and reports the result of each")
+                .takes_value(false)
+                .required(false),
+        )
+        .arg(
+            Arg::new("run-bytecode")
+                .long("run-bytecode")
+                .value_name("PATH")
+                .help("Runs the bytecode written by --emit-bytecode instead of compiling a program")
+                .takes_value(true)
+                .required(false),
+        )
+        .group(
+            ArgGroup::new("input")
+                .args(&["file", "eval", "batch", "run-bytecode"])
                 .required(true),
         )
         .arg(
@@ -16,7 +68,7 @@ pub fn parse_arguments() -> ArgMatches {
                 .short('d')
                 .long("debug")
                 .value_name("DEBUG")
-                .help("Displays debugging prints throughout the process")
+                .help("Displays debugging prints throughout the process, also enabled by RAOUL_DEBUG=1")
                 .default_value("false")
                 .takes_value(false)
                 .required(false),
@@ -31,5 +83,130 @@ pub fn parse_arguments() -> ArgMatches {
                 .takes_value(false)
                 .required(false),
         )
+        .arg(
+            Arg::new("ast-only")
+                .long("ast-only")
+                .value_name("AST_ONLY")
+                .help("Prints the AST of the program and exits without compiling or running it")
+                .default_value("false")
+                .takes_value(false)
+                .required(false),
+        )
+        .arg(
+            Arg::new("dump-symbols")
+                .long("dump-symbols")
+                .value_name("DUMP_SYMBOLS")
+                .help("Prints the variables of each function, sorted by address, before running the program")
+                .default_value("false")
+                .takes_value(false)
+                .required(false),
+        )
+        .arg(
+            Arg::new("bool-as-int")
+                .long("bool-as-int")
+                .value_name("BOOL_AS_INT")
+                .help("Prints booleans as 1 or 0 instead of true or false")
+                .default_value("false")
+                .takes_value(false)
+                .required(false),
+        )
+        .arg(
+            Arg::new("fail-fast")
+                .long("fail-fast")
+                .value_name("FAIL_FAST")
+                .help("Stops compiling at the first error instead of reporting all of them")
+                .default_value("false")
+                .takes_value(false)
+                .required(false),
+        )
+        .arg(
+            Arg::new("optimize")
+                .short('O')
+                .long("optimize")
+                .value_name("OPTIMIZE")
+                .help("Reuses the result of repeated expressions instead of computing them again")
+                .default_value("false")
+                .takes_value(false)
+                .required(false),
+        )
+        .arg(
+            Arg::new("flush")
+                .long("flush")
+                .value_name("FLUSH")
+                .help("Writes the output of every print immediately instead of once per line")
+                .default_value("false")
+                .takes_value(false)
+                .required(false),
+        )
+        .arg(
+            Arg::new("line-endings")
+                .long("line-endings")
+                .value_name("ENDING")
+                .help("Line endings used by the output of the program")
+                .possible_values(["lf", "crlf"])
+                .default_value("lf")
+                .takes_value(true)
+                .required(false),
+        )
+        .arg(
+            Arg::new("bom")
+                .long("bom")
+                .value_name("BOM")
+                .help("Starts the output of the program with a UTF-8 byte order mark")
+                .default_value("false")
+                .takes_value(false)
+                .required(false),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .value_name("SEED")
+                .help("Seeds `random` and `random_int` so runs are reproducible")
+                .takes_value(true)
+                .validator(|seed| seed.parse::<u64>())
+                .required(false),
+        )
+        .arg(
+            Arg::new("stack-cap")
+                .long("stack-cap")
+                .value_name("SIZE")
+                .help("Most values and nested calls the stack can hold before overflowing")
+                .takes_value(true)
+                .validator(|size| size.parse::<usize>())
+                .required(false),
+        )
+        .arg(
+            Arg::new("max-steps")
+                .long("max-steps")
+                .value_name("STEPS")
+                .help("Stops the program after executing STEPS quads")
+                .takes_value(true)
+                .validator(|steps| steps.parse::<usize>())
+                .required(false),
+        )
+        .arg(
+            Arg::new("emit-dot")
+                .long("emit-dot")
+                .value_name("PATH")
+                .help("Writes the control-flow graph of the quads to PATH in Graphviz DOT format")
+                .takes_value(true)
+                .required(false),
+        )
+        .arg(
+            Arg::new("emit-bytecode")
+                .long("emit-bytecode")
+                .value_name("PATH")
+                .help("Writes the compiled program to PATH instead of running it")
+                .takes_value(true)
+                .required(false),
+        )
+        .arg(
+            Arg::new("args")
+                .value_name("ARGS")
+                .help("Arguments given to the program, read with `argv`")
+                .multiple_values(true)
+                .last(true)
+                .required(false),
+        )
         .get_matches()
 }
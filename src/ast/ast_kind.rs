@@ -1,4 +1,4 @@
-use super::{BoxedNode, Nodes};
+use super::{AstNode, BoxedNode, Nodes};
 use crate::{
     dir_func::variable::Dimensions,
     enums::{Operator, Types},
@@ -18,6 +18,10 @@ pub enum AstNodeKind<'a> {
         dim1: usize,
         dim2: Option<usize>,
     },
+    FilledArray {
+        value: BoxedNode<'a>,
+        size: usize,
+    },
     ArrayVal {
         name: String,
         idx_1: BoxedNode<'a>,
@@ -32,6 +36,11 @@ pub enum AstNodeKind<'a> {
         operator: Operator,
         operand: BoxedNode<'a>,
     },
+    /// `i++` or `i--`, with `Inc` or `Dec` as the operator
+    Increment {
+        operator: Operator,
+        assignee: BoxedNode<'a>,
+    },
     BinaryOperation {
         operator: Operator,
         lhs: BoxedNode<'a>,
@@ -39,8 +48,9 @@ pub enum AstNodeKind<'a> {
     },
     Main {
         assignments: Nodes<'a>,
-        body: Nodes<'a>,
+        body: Option<Nodes<'a>>,
         functions: Nodes<'a>,
+        records: Nodes<'a>,
     },
     Argument {
         arg_type: Types,
@@ -51,9 +61,26 @@ pub enum AstNodeKind<'a> {
         body: Nodes<'a>,
         name: String,
         return_type: Types,
+        /// Types of the values returned after the first one
+        extra_returns: Vec<Types>,
+    },
+    Write {
+        exprs: Nodes<'a>,
+        separator: Option<String>,
+    },
+    Read(Option<String>),
+    ReadBool(Option<String>),
+    Argv(BoxedNode<'a>),
+    Tuple(Nodes<'a>),
+    MultiAssignment {
+        assignees: Nodes<'a>,
+        call: BoxedNode<'a>,
+    },
+    Random,
+    RandomInt {
+        low: BoxedNode<'a>,
+        high: BoxedNode<'a>,
     },
-    Write(Nodes<'a>),
-    Read,
     Decision {
         expr: BoxedNode<'a>,
         statements: Nodes<'a>,
@@ -64,17 +91,54 @@ pub enum AstNodeKind<'a> {
         expr: BoxedNode<'a>,
         statements: Nodes<'a>,
     },
+    Repeat {
+        count: BoxedNode<'a>,
+        statements: Nodes<'a>,
+    },
+    Try {
+        body: Nodes<'a>,
+        handler: Nodes<'a>,
+    },
     For {
         assignment: BoxedNode<'a>,
         expr: BoxedNode<'a>,
         statements: Nodes<'a>,
     },
+    /// `for name in array`, binding a copy of each element to `name`
+    ForEach {
+        name: String,
+        array: String,
+        statements: Nodes<'a>,
+    },
+    Match {
+        expr: BoxedNode<'a>,
+        arms: Vec<(AstNode<'a>, Nodes<'a>)>,
+        default: Option<Nodes<'a>>,
+    },
     FuncCall {
         name: String,
         exprs: Nodes<'a>,
     },
-    Return(BoxedNode<'a>),
+    Return(Option<BoxedNode<'a>>),
+    Assert {
+        expr: BoxedNode<'a>,
+        message: Option<String>,
+    },
+    Error(String),
+    Append {
+        name: String,
+        value: BoxedNode<'a>,
+    },
     ReadCSV(BoxedNode<'a>),
+    ColumnsOf {
+        name: String,
+        size: usize,
+    },
+    Split {
+        text: BoxedNode<'a>,
+        separator: BoxedNode<'a>,
+        size: usize,
+    },
     PureDataframeOp {
         name: String,
         operator: Operator,
@@ -98,6 +162,26 @@ pub enum AstNodeKind<'a> {
         column: BoxedNode<'a>,
         name: String,
         bins: BoxedNode<'a>,
+        /// Whether the bars show the share of the values instead of the count
+        density: Option<BoxedNode<'a>>,
+    },
+    Describe(String),
+    Show {
+        name: String,
+        rows: Option<BoxedNode<'a>>,
+    },
+    CastColumn {
+        name: String,
+        column: BoxedNode<'a>,
+        data_type: Types,
+    },
+    RecordDeclaration {
+        name: String,
+        fields: Vec<(String, Types)>,
+    },
+    Record {
+        name: String,
+        fields: Vec<(String, AstNode<'a>)>,
     },
 }
 
@@ -144,6 +228,7 @@ impl fmt::Debug for AstNodeKind<'_> {
             } => {
                 write!(f, "ArrayDeclaration({data_type:?}, {dim1}, {dim2:?})")
             }
+            Self::FilledArray { value, size } => write!(f, "FilledArray({value:?}, {size})"),
             Self::ArrayVal { name, idx_1, idx_2 } => {
                 write!(f, "ArrayVal({name}, {idx_1:?}, {idx_2:?})")
             }
@@ -158,26 +243,66 @@ impl fmt::Debug for AstNodeKind<'_> {
             } => {
                 write!(f, "Unary({:?}, {:?})", operation, operand)
             }
+            Self::Increment { operator, assignee } => {
+                write!(f, "Increment({:?}, {:?})", operator, assignee)
+            }
             Self::Main {
                 assignments,
                 body,
                 functions,
-            } => write!(f, "Main(({assignments:#?}, {:#?}, {:#?}))", functions, body),
+                records,
+            } => {
+                write!(f, "Main((")?;
+                // Only programs that declare records show them
+                if !records.is_empty() {
+                    write!(f, "{records:#?}, ")?;
+                }
+                write!(f, "{assignments:#?}, {functions:#?}, ")?;
+                match body {
+                    Some(body) => write!(f, "{body:#?}))"),
+                    None => write!(f, "None))"),
+                }
+            }
             Self::Argument { arg_type, name } => write!(f, "Argument({:?}, {})", arg_type, name),
             Self::Function {
                 arguments,
                 body,
                 name,
                 return_type,
-            } => {
+                extra_returns,
+            } if extra_returns.is_empty() => {
                 write!(
                     f,
                     "Function({}, {:?}, {:?}, {:#?})",
                     name, return_type, arguments, body
                 )
             }
-            Self::Write(exprs) => write!(f, "Write({:?})", exprs),
-            Self::Read => write!(f, "Read"),
+            Self::Function {
+                arguments,
+                body,
+                name,
+                return_type,
+                extra_returns,
+            } => {
+                let return_types: Vec<_> =
+                    Some(return_type).into_iter().chain(extra_returns).collect();
+                write!(
+                    f,
+                    "Function({name}, {return_types:?}, {arguments:?}, {body:#?})"
+                )
+            }
+            Self::Write {
+                exprs,
+                separator: None,
+            } => write!(f, "Write({:?})", exprs),
+            Self::Write {
+                exprs,
+                separator: Some(separator),
+            } => write!(f, "Write({exprs:?}, {separator:?})"),
+            Self::Read(None) => write!(f, "Read"),
+            Self::Read(Some(prompt)) => write!(f, "Read({prompt:?})"),
+            Self::ReadBool(None) => write!(f, "ReadBool"),
+            Self::ReadBool(Some(prompt)) => write!(f, "ReadBool({prompt:?})"),
             Self::BinaryOperation { operator, lhs, rhs } => {
                 write!(f, "BinaryOperation({:?}, {:?}, {:?})", operator, lhs, rhs)
             }
@@ -190,6 +315,10 @@ impl fmt::Debug for AstNodeKind<'_> {
             }
             Self::ElseBlock(statements) => write!(f, "ElseBlock({:?})", statements),
             Self::While { expr, statements } => write!(f, "While({:?}, {:?})", expr, statements),
+            Self::Repeat { count, statements } => {
+                write!(f, "Repeat({:?}, {:?})", count, statements)
+            }
+            Self::Try { body, handler } => write!(f, "Try({body:?}, {handler:?})"),
             Self::For {
                 expr,
                 statements,
@@ -197,9 +326,36 @@ impl fmt::Debug for AstNodeKind<'_> {
             } => {
                 write!(f, "For({expr:?}, {statements:?}, {assignment:?})")
             }
+            Self::ForEach {
+                name,
+                array,
+                statements,
+            } => write!(f, "ForEach({name}, {array}, {statements:?})"),
+            Self::Match {
+                expr,
+                arms,
+                default,
+            } => write!(f, "Match({expr:?}, {arms:?}, {default:?})"),
             Self::FuncCall { name, exprs } => write!(f, "FunctionCall({name}, {exprs:?})"),
-            Self::Return(expr) => write!(f, "Return({expr:?})"),
+            Self::Return(None) => write!(f, "Return"),
+            Self::Return(Some(expr)) => write!(f, "Return({expr:?})"),
+            Self::Assert { expr, message } => write!(f, "Assert({expr:?}, {message:?})"),
+            Self::Error(message) => write!(f, "Error({message:?})"),
+            Self::Append { name, value } => write!(f, "Append({name}, {value:?})"),
+            Self::Argv(index) => write!(f, "Argv({index:?})"),
+            Self::Tuple(values) => write!(f, "Tuple({values:?})"),
+            Self::MultiAssignment { assignees, call } => {
+                write!(f, "MultiAssignment({assignees:?}, {call:?})")
+            }
+            Self::Random => write!(f, "Random"),
+            Self::RandomInt { low, high } => write!(f, "RandomInt({low:?}, {high:?})"),
             Self::ReadCSV(file) => write!(f, "ReadCSV({file:?})"),
+            Self::ColumnsOf { name, size } => write!(f, "ColumnsOf({name}, {size})"),
+            Self::Split {
+                text,
+                separator,
+                size,
+            } => write!(f, "Split({text:?}, {separator:?}, {size})"),
             Self::PureDataframeOp { name, operator } => {
                 write!(f, "PureDataframeOp({operator:?}, {name})")
             }
@@ -222,20 +378,50 @@ impl fmt::Debug for AstNodeKind<'_> {
                 column_1,
                 column_2,
             } => write!(f, "Plot({name}, {column_1:?}, {column_2:?})"),
-            Self::Histogram { column, name, bins } => {
-                write!(f, "Histogram({column:?}, {name}, {bins:?})")
+            Self::Histogram {
+                column,
+                name,
+                bins,
+                density: None,
+            } => write!(f, "Histogram({column:?}, {name}, {bins:?})"),
+            Self::Histogram {
+                column,
+                name,
+                bins,
+                density: Some(density),
+            } => write!(f, "Histogram({column:?}, {name}, {bins:?}, {density:?})"),
+            Self::Describe(name) => write!(f, "Describe({name})"),
+            Self::Show { name, rows } => write!(f, "Show({name}, {rows:?})"),
+            Self::CastColumn {
+                name,
+                column,
+                data_type,
+            } => write!(f, "CastColumn({name}, {column:?}, {data_type:?})"),
+            Self::RecordDeclaration { name, fields } => {
+                write!(f, "RecordDeclaration({name}, {fields:?})")
             }
+            Self::Record { name, fields } => write!(f, "Record({name}, {fields:?})"),
         }
     }
 }
 
 impl<'a> AstNodeKind<'a> {
     pub fn is_array(&self) -> bool {
-        matches!(self, Self::Array(_) | Self::ArrayDeclaration { .. })
+        matches!(
+            self,
+            Self::Array(_)
+                | Self::ArrayDeclaration { .. }
+                | Self::FilledArray { .. }
+                | Self::ColumnsOf { .. }
+                | Self::Split { .. }
+        )
     }
 
     pub fn is_declaration(&self) -> bool {
-        matches!(self, Self::Assignment { .. } | Self::Argument { .. })
+        matches!(
+            self,
+            Self::Assignment { .. } | Self::Argument { .. } | Self::MultiAssignment { .. }
+        )
     }
 
     pub fn get_dimensions(&self) -> Result<Dimensions, Dimensions> {
@@ -244,9 +430,12 @@ impl<'a> AstNodeKind<'a> {
         }
         match self {
             Self::ArrayDeclaration { dim1, dim2, .. } => Ok((Some(*dim1), *dim2)),
+            Self::FilledArray { size, .. }
+            | Self::ColumnsOf { size, .. }
+            | Self::Split { size, .. } => Ok((Some(*size), None)),
             Self::Array(exprs) => {
                 let dim1 = Some(exprs.len());
-                let dim2 = exprs.get(0).unwrap().get_dimensions()?.0;
+                let dim2 = exprs.first().unwrap().get_dimensions()?.0;
                 let errors: Vec<_> = exprs
                     .iter()
                     .map(|expr| {
@@ -262,7 +451,7 @@ impl<'a> AstNodeKind<'a> {
                 if errors.is_empty() {
                     Ok((dim1, dim2))
                 } else {
-                    Err(*errors.get(0).unwrap())
+                    Err(*errors.first().unwrap())
                 }
             }
             _ => unreachable!("{self:?}"),
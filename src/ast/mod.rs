@@ -1,11 +1,13 @@
 #[allow(clippy::module_name_repetitions)]
 pub mod ast_kind;
+pub mod printer;
+pub mod visitor;
 
-use crate::dir_func::variable::Dimensions;
+use crate::{dir_func::variable::Dimensions, enums::Operator};
 
 use self::ast_kind::AstNodeKind;
 use pest::Span;
-use std::fmt;
+use std::{collections::HashMap, fmt};
 
 #[derive(PartialEq, Clone)]
 #[allow(clippy::module_name_repetitions)]
@@ -49,22 +51,336 @@ impl<'a> AstNode<'a> {
         match &v.kind {
             AstNodeKind::Decision { statements, .. }
             | AstNodeKind::ElseBlock(statements)
-            | AstNodeKind::While { statements, .. } => {
+            | AstNodeKind::While { statements, .. }
+            | AstNodeKind::Repeat { statements, .. } => {
                 statements.iter().flat_map(AstNode::expand_node).collect()
             }
+            AstNodeKind::Try { body, handler } => body
+                .iter()
+                .chain(handler)
+                .flat_map(AstNode::expand_node)
+                .collect(),
             AstNodeKind::For {
                 statements,
                 assignment,
                 ..
-            } => vec![*assignment.clone()]
+            } => [*assignment.clone()]
                 .iter()
                 .chain(statements)
                 .flat_map(AstNode::expand_node)
                 .collect(),
+            // The element is declared as if it was assigned the first one
+            AstNodeKind::ForEach {
+                name,
+                array,
+                statements,
+            } => {
+                let value = AstNodeKind::ArrayVal {
+                    name: array.clone(),
+                    idx_1: Box::new(AstNode::new(AstNodeKind::Integer(0), &v.span)),
+                    idx_2: None,
+                };
+                let assignment = AstNodeKind::Assignment {
+                    assignee: Box::new(AstNode::new(AstNodeKind::Id(name.clone()), &v.span)),
+                    global: false,
+                    value: Box::new(AstNode::new(value, &v.span)),
+                };
+                [AstNode::new(assignment, &v.span)]
+                    .iter()
+                    .chain(statements)
+                    .flat_map(AstNode::expand_node)
+                    .collect()
+            }
+            AstNodeKind::Match { arms, default, .. } => arms
+                .iter()
+                .flat_map(|(_, statements)| statements)
+                .chain(default.iter().flatten())
+                .flat_map(AstNode::expand_node)
+                .collect(),
             _ => vec![v.clone()],
         }
     }
 
+    /// Direct children of the node, in source order
+    pub fn children(&self) -> Vec<&AstNode<'a>> {
+        match &self.kind {
+            AstNodeKind::Id(_)
+            | AstNodeKind::Integer(_)
+            | AstNodeKind::Float(_)
+            | AstNodeKind::String(_)
+            | AstNodeKind::Bool(_)
+            | AstNodeKind::ArrayDeclaration { .. }
+            | AstNodeKind::Argument { .. }
+            | AstNodeKind::Read(_)
+            | AstNodeKind::ReadBool(_)
+            | AstNodeKind::Random
+            | AstNodeKind::Error(_)
+            | AstNodeKind::Return(None)
+            | AstNodeKind::ColumnsOf { .. }
+            | AstNodeKind::Describe(_)
+            | AstNodeKind::RecordDeclaration { .. }
+            | AstNodeKind::PureDataframeOp { .. } => vec![],
+            AstNodeKind::Array(nodes)
+            | AstNodeKind::Write { exprs: nodes, .. }
+            | AstNodeKind::ElseBlock(nodes)
+            | AstNodeKind::Tuple(nodes)
+            | AstNodeKind::FuncCall { exprs: nodes, .. } => nodes.iter().collect(),
+            AstNodeKind::MultiAssignment { assignees, call } => {
+                assignees.iter().chain(Some(call.as_ref())).collect()
+            }
+            AstNodeKind::ArrayVal { idx_1, idx_2, .. } => [idx_1]
+                .into_iter()
+                .chain(idx_2)
+                .map(AsRef::as_ref)
+                .collect(),
+            AstNodeKind::Assignment {
+                assignee, value, ..
+            } => vec![assignee, value],
+            AstNodeKind::UnaryOperation { operand, .. }
+            | AstNodeKind::Increment {
+                assignee: operand, ..
+            } => vec![operand],
+            AstNodeKind::BinaryOperation { lhs, rhs, .. } => vec![lhs, rhs],
+            AstNodeKind::Main {
+                assignments,
+                body,
+                functions,
+                records,
+            } => records
+                .iter()
+                .chain(assignments)
+                .chain(functions)
+                .chain(body.iter().flatten())
+                .collect(),
+            AstNodeKind::Function {
+                arguments, body, ..
+            } => arguments.iter().chain(body).collect(),
+            AstNodeKind::Decision {
+                expr,
+                statements,
+                else_block,
+            } => [expr.as_ref()]
+                .into_iter()
+                .chain(statements)
+                .chain(else_block.as_deref())
+                .collect(),
+            AstNodeKind::While { expr, statements }
+            | AstNodeKind::Repeat {
+                count: expr,
+                statements,
+            } => [expr.as_ref()].into_iter().chain(statements).collect(),
+            AstNodeKind::Try { body, handler } => body.iter().chain(handler).collect(),
+            AstNodeKind::ForEach { statements, .. } => statements.iter().collect(),
+            AstNodeKind::For {
+                assignment,
+                expr,
+                statements,
+            } => [assignment.as_ref(), expr.as_ref()]
+                .into_iter()
+                .chain(statements)
+                .collect(),
+            AstNodeKind::Match {
+                expr,
+                arms,
+                default,
+            } => [expr.as_ref()]
+                .into_iter()
+                .chain(
+                    arms.iter()
+                        .flat_map(|(value, statements)| [value].into_iter().chain(statements)),
+                )
+                .chain(default.iter().flatten())
+                .collect(),
+            AstNodeKind::Return(Some(expr))
+            | AstNodeKind::Assert { expr, .. }
+            | AstNodeKind::Append { value: expr, .. }
+            | AstNodeKind::ReadCSV(expr)
+            | AstNodeKind::Argv(expr)
+            | AstNodeKind::FilledArray { value: expr, .. }
+            | AstNodeKind::UnaryDataframeOp { column: expr, .. } => vec![expr],
+            AstNodeKind::Correlation {
+                column_1, column_2, ..
+            }
+            | AstNodeKind::Plot {
+                column_1, column_2, ..
+            } => vec![column_1, column_2],
+            AstNodeKind::Histogram {
+                column,
+                bins,
+                density,
+                ..
+            } => {
+                let mut children: Vec<&AstNode> = vec![column, bins];
+                children.extend(density.as_deref());
+                children
+            }
+            AstNodeKind::CastColumn { column, .. } => vec![column],
+            AstNodeKind::RandomInt { low, high } => vec![low, high],
+            AstNodeKind::Split {
+                text, separator, ..
+            } => vec![text, separator],
+            AstNodeKind::Show { rows, .. } => rows.iter().map(AsRef::as_ref).collect(),
+            AstNodeKind::Record { fields, .. } => fields.iter().map(|(_, value)| value).collect(),
+        }
+    }
+
+    /// Direct children of the node, in source order, for rewriting them
+    pub fn children_mut(&mut self) -> Vec<&mut AstNode<'a>> {
+        match &mut self.kind {
+            AstNodeKind::Id(_)
+            | AstNodeKind::Integer(_)
+            | AstNodeKind::Float(_)
+            | AstNodeKind::String(_)
+            | AstNodeKind::Bool(_)
+            | AstNodeKind::ArrayDeclaration { .. }
+            | AstNodeKind::Argument { .. }
+            | AstNodeKind::Read(_)
+            | AstNodeKind::ReadBool(_)
+            | AstNodeKind::Random
+            | AstNodeKind::Error(_)
+            | AstNodeKind::Return(None)
+            | AstNodeKind::ColumnsOf { .. }
+            | AstNodeKind::Describe(_)
+            | AstNodeKind::RecordDeclaration { .. }
+            | AstNodeKind::PureDataframeOp { .. } => vec![],
+            AstNodeKind::Array(nodes)
+            | AstNodeKind::Write { exprs: nodes, .. }
+            | AstNodeKind::ElseBlock(nodes)
+            | AstNodeKind::Tuple(nodes)
+            | AstNodeKind::FuncCall { exprs: nodes, .. } => nodes.iter_mut().collect(),
+            AstNodeKind::MultiAssignment { assignees, call } => {
+                assignees.iter_mut().chain(Some(call.as_mut())).collect()
+            }
+            AstNodeKind::ArrayVal { idx_1, idx_2, .. } => [idx_1]
+                .into_iter()
+                .chain(idx_2)
+                .map(AsMut::as_mut)
+                .collect(),
+            AstNodeKind::Assignment {
+                assignee, value, ..
+            } => vec![assignee, value],
+            AstNodeKind::UnaryOperation { operand, .. }
+            | AstNodeKind::Increment {
+                assignee: operand, ..
+            } => vec![operand],
+            AstNodeKind::BinaryOperation { lhs, rhs, .. } => vec![lhs, rhs],
+            AstNodeKind::Main {
+                assignments,
+                body,
+                functions,
+                records,
+            } => records
+                .iter_mut()
+                .chain(assignments)
+                .chain(functions)
+                .chain(body.iter_mut().flatten())
+                .collect(),
+            AstNodeKind::Function {
+                arguments, body, ..
+            } => arguments.iter_mut().chain(body).collect(),
+            AstNodeKind::Decision {
+                expr,
+                statements,
+                else_block,
+            } => [expr.as_mut()]
+                .into_iter()
+                .chain(statements)
+                .chain(else_block.as_deref_mut())
+                .collect(),
+            AstNodeKind::While { expr, statements }
+            | AstNodeKind::Repeat {
+                count: expr,
+                statements,
+            } => [expr.as_mut()].into_iter().chain(statements).collect(),
+            AstNodeKind::Try { body, handler } => body.iter_mut().chain(handler).collect(),
+            AstNodeKind::ForEach { statements, .. } => statements.iter_mut().collect(),
+            AstNodeKind::For {
+                assignment,
+                expr,
+                statements,
+            } => [assignment.as_mut(), expr.as_mut()]
+                .into_iter()
+                .chain(statements)
+                .collect(),
+            AstNodeKind::Match {
+                expr,
+                arms,
+                default,
+            } => [expr.as_mut()]
+                .into_iter()
+                .chain(
+                    arms.iter_mut()
+                        .flat_map(|(value, statements)| [value].into_iter().chain(statements)),
+                )
+                .chain(default.iter_mut().flatten())
+                .collect(),
+            AstNodeKind::Return(Some(expr))
+            | AstNodeKind::Assert { expr, .. }
+            | AstNodeKind::Append { value: expr, .. }
+            | AstNodeKind::ReadCSV(expr)
+            | AstNodeKind::Argv(expr)
+            | AstNodeKind::FilledArray { value: expr, .. }
+            | AstNodeKind::UnaryDataframeOp { column: expr, .. } => vec![expr],
+            AstNodeKind::Correlation {
+                column_1, column_2, ..
+            }
+            | AstNodeKind::Plot {
+                column_1, column_2, ..
+            } => vec![column_1, column_2],
+            AstNodeKind::Histogram {
+                column,
+                bins,
+                density,
+                ..
+            } => {
+                let mut children: Vec<&mut AstNode> = vec![column, bins];
+                children.extend(density.as_deref_mut());
+                children
+            }
+            AstNodeKind::CastColumn { column, .. } => vec![column],
+            AstNodeKind::RandomInt { low, high } => vec![low, high],
+            AstNodeKind::Split {
+                text, separator, ..
+            } => vec![text, separator],
+            AstNodeKind::Show { rows, .. } => rows.iter_mut().map(AsMut::as_mut).collect(),
+            AstNodeKind::Record { fields, .. } => {
+                fields.iter_mut().map(|(_, value)| value).collect()
+            }
+        }
+    }
+
+    /// Renames the calls to the given functions, keyed by name and amount of
+    /// arguments, here and in every descendant
+    pub fn rename_calls(&mut self, renames: &HashMap<(String, usize), String>) {
+        if let AstNodeKind::FuncCall { name, exprs } = &mut self.kind {
+            if let Some(new_name) = renames.get(&(name.clone(), exprs.len())) {
+                *name = new_name.clone();
+            }
+        }
+        for child in self.children_mut() {
+            child.rename_calls(renames);
+        }
+    }
+
+    /// Value of an integer expression made only of literals, evaluated while
+    /// compiling. `None` if it uses anything else or overflows
+    pub fn const_int(&self) -> Option<i64> {
+        match &self.kind {
+            AstNodeKind::Integer(value) => Some(*value),
+            AstNodeKind::BinaryOperation { operator, lhs, rhs } => {
+                let (lhs, rhs) = (lhs.const_int()?, rhs.const_int()?);
+                match operator {
+                    Operator::Sum => lhs.checked_add(rhs),
+                    Operator::Minus => lhs.checked_sub(rhs),
+                    Operator::Times => lhs.checked_mul(rhs),
+                    Operator::Div => lhs.checked_div(rhs),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
     pub fn expand_array(&self) -> &Nodes<'a> {
         match &self.kind {
             AstNodeKind::Array(exprs) => exprs,
@@ -100,3 +416,6 @@ impl fmt::Debug for AstNode<'_> {
 
 pub type BoxedNode<'a> = Box<AstNode<'a>>;
 pub type Nodes<'a> = Vec<AstNode<'a>>;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,505 @@
+use super::{ast_kind::AstNodeKind, AstNode};
+use crate::{
+    enums::Operator,
+    parser::{COLUMNS_OF_SIZE, SPLIT_PARTS},
+};
+
+const INDENT: &str = "  ";
+/// Precedence of the nodes that are never split by an operator
+const ATOM: u8 = u8::MAX;
+/// `NOT` binds tighter than `AND` but looser than the comparisons
+const NOT: u8 = 5;
+
+/// Turns a parsed program back into source code, with two spaces of
+/// indentation, spaces around the operators and every body inside a block.
+/// Comments aren't part of the AST, so they are dropped.
+pub fn format_program(ast: &AstNode) -> String {
+    let (assignments, body, functions, records) = match &ast.kind {
+        AstNodeKind::Main {
+            assignments,
+            body,
+            functions,
+            records,
+        } => (assignments, body, functions, records),
+        node => unreachable!("{node:?} is not a program"),
+    };
+    let mut sections = Vec::new();
+    for record in records {
+        sections.push(record_declaration(record));
+    }
+    if !assignments.is_empty() {
+        let lines: Vec<_> = assignments
+            .iter()
+            .map(|node| match &node.kind {
+                AstNodeKind::Assignment {
+                    assignee, value, ..
+                } => format!("{} = {};", expression(assignee), expression(value)),
+                node => unreachable!("{node:?} is not a global assignment"),
+            })
+            .collect();
+        sections.push(lines.join("\n"));
+    }
+    for function in functions.iter().filter(|node| scope(node).is_none()) {
+        let mut printer = Printer::new(functions);
+        printer.function(function);
+        sections.push(printer.finish());
+    }
+    if let Some(body) = body {
+        let mut printer = Printer::new(functions);
+        printer.line("func main(): void {");
+        printer.body("main", body);
+        printer.line("}");
+        sections.push(printer.finish());
+    }
+    let mut source = sections.join("\n\n");
+    source.push('\n');
+    source
+}
+
+/// Writes the statements, keeping track of how deep they are nested
+struct Printer<'n, 'a> {
+    /// Every function of the program, the nested ones are printed back inside
+    /// the function that declared them
+    functions: &'n [AstNode<'a>],
+    lines: Vec<String>,
+    depth: usize,
+}
+
+impl<'n, 'a> Printer<'n, 'a> {
+    fn new(functions: &'n [AstNode<'a>]) -> Self {
+        Self {
+            functions,
+            lines: Vec::new(),
+            depth: 0,
+        }
+    }
+
+    fn finish(self) -> String {
+        self.lines.join("\n")
+    }
+
+    fn line(&mut self, text: &str) {
+        self.lines
+            .push(format!("{}{text}", INDENT.repeat(self.depth)));
+    }
+
+    /// Writes the nested functions of `name` and then its statements, one
+    /// level deeper
+    fn body(&mut self, name: &str, statements: &[AstNode]) {
+        self.depth += 1;
+        let functions = self.functions;
+        for function in functions.iter().filter(|node| scope(node) == Some(name)) {
+            self.function(function);
+        }
+        statements.iter().for_each(|node| self.statement(node));
+        self.depth -= 1;
+    }
+
+    fn block(&mut self, header: &str, statements: &[AstNode]) {
+        self.line(&format!("{header} {{"));
+        self.body("", statements);
+        self.line("}");
+    }
+
+    fn function(&mut self, node: &AstNode) {
+        if let AstNodeKind::Function {
+            arguments,
+            body,
+            name,
+            return_type,
+            extra_returns,
+        } = &node.kind
+        {
+            let arguments: Vec<_> = arguments
+                .iter()
+                .map(|argument| match &argument.kind {
+                    AstNodeKind::Argument { arg_type, name } => {
+                        format!("{name}: {arg_type}")
+                    }
+                    node => unreachable!("{node:?} is not an argument"),
+                })
+                .collect();
+            let returns = match extra_returns.is_empty() {
+                true => return_type.to_string(),
+                false => {
+                    let types: Vec<_> = std::iter::once(return_type)
+                        .chain(extra_returns)
+                        .map(ToString::to_string)
+                        .collect();
+                    format!("({})", types.join(", "))
+                }
+            };
+            let header = format!(
+                "func {}({}): {returns} {{",
+                local_name(name),
+                arguments.join(", ")
+            );
+            self.line(&header);
+            self.body(name, body);
+            self.line("}");
+        }
+    }
+
+    /// Writes an `if`, with `prefix` being what closes the previous branch
+    /// when it's part of an `else if`
+    fn decision(&mut self, node: &AstNode, prefix: &str) {
+        if let AstNodeKind::Decision {
+            expr,
+            statements,
+            else_block,
+        } = &node.kind
+        {
+            self.line(&format!("{prefix}if ({}) {{", expression(expr)));
+            self.body("", statements);
+            match else_block.as_deref() {
+                None => self.line("}"),
+                Some(AstNode {
+                    kind: AstNodeKind::ElseBlock(statements),
+                    ..
+                }) => {
+                    self.line("} else {");
+                    self.body("", statements);
+                    self.line("}");
+                }
+                Some(decision) => self.decision(decision, "} else "),
+            }
+        }
+    }
+
+    fn statement(&mut self, node: &AstNode) {
+        match &node.kind {
+            AstNodeKind::Decision { .. } => self.decision(node, ""),
+            AstNodeKind::While { expr, statements } => {
+                self.block(&format!("while ({})", expression(expr)), statements);
+            }
+            AstNodeKind::Repeat { count, statements } => {
+                self.block(&format!("repeat {}", expression(count)), statements);
+            }
+            AstNodeKind::For {
+                assignment,
+                expr,
+                statements,
+            } => {
+                let stop = match &expr.kind {
+                    AstNodeKind::BinaryOperation { rhs, .. } => expression(rhs),
+                    node => unreachable!("{node:?} is not the condition of a for"),
+                };
+                let header = format!("for ({} to {stop})", inline_statement(assignment));
+                self.block(&header, statements);
+            }
+            AstNodeKind::ForEach {
+                name,
+                array,
+                statements,
+            } => self.block(&format!("for {name} in {array}"), statements),
+            AstNodeKind::Try { body, handler } => {
+                self.line("try {");
+                self.body("", body);
+                self.line("} catch {");
+                self.body("", handler);
+                self.line("}");
+            }
+            AstNodeKind::Match {
+                expr,
+                arms,
+                default,
+            } => {
+                self.line(&format!("match {} {{", expression(expr)));
+                self.depth += 1;
+                for (value, statements) in arms {
+                    self.arm(&expression(value), statements);
+                }
+                if let Some(statements) = default {
+                    self.arm("_", statements);
+                }
+                self.depth -= 1;
+                self.line("}");
+            }
+            _ => self.line(&format!("{};", inline_statement(node))),
+        }
+    }
+
+    /// Arms with a single inline statement are kept in one line
+    fn arm(&mut self, value: &str, statements: &[AstNode]) {
+        match statements {
+            [statement] if !is_block_statement(statement) => {
+                self.line(&format!("{value} => {};", inline_statement(statement)));
+            }
+            _ => self.block(&format!("{value} =>"), statements),
+        }
+    }
+}
+
+fn is_block_statement(node: &AstNode) -> bool {
+    matches!(
+        node.kind,
+        AstNodeKind::Decision { .. }
+            | AstNodeKind::While { .. }
+            | AstNodeKind::Repeat { .. }
+            | AstNodeKind::For { .. }
+            | AstNodeKind::ForEach { .. }
+            | AstNodeKind::Try { .. }
+            | AstNodeKind::Match { .. }
+    )
+}
+
+/// Name of the function that declared `node`, if it was nested
+fn scope<'n>(node: &'n AstNode) -> Option<&'n str> {
+    match &node.kind {
+        AstNodeKind::Function { name, .. } => name.rsplit_once("::").map(|(scope, _)| scope),
+        _ => None,
+    }
+}
+
+/// Name given in the source to a function, without the scope added when it
+/// was hoisted
+fn local_name(name: &str) -> &str {
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+fn record_declaration(node: &AstNode) -> String {
+    match &node.kind {
+        AstNodeKind::RecordDeclaration { name, fields } => {
+            let fields: Vec<_> = fields
+                .iter()
+                .map(|(field, data_type)| format!("{INDENT}{field} {data_type},\n"))
+                .collect();
+            format!("struct {name} {{\n{}}}", fields.concat())
+        }
+        node => unreachable!("{node:?} is not a struct"),
+    }
+}
+
+/// Statements that end with a semicolon, without it
+fn inline_statement(node: &AstNode) -> String {
+    match &node.kind {
+        AstNodeKind::Assignment {
+            assignee,
+            global,
+            value,
+        } => {
+            let global = if *global { "global " } else { "" };
+            format!("{global}{} = {}", expression(assignee), expression(value))
+        }
+        AstNodeKind::MultiAssignment { assignees, call } => {
+            format!("{} = {}", list(assignees), expression(call))
+        }
+        AstNodeKind::Increment { operator, assignee } => {
+            let step = if *operator == Operator::Dec {
+                "--"
+            } else {
+                "++"
+            };
+            format!("{}{step}", expression(assignee))
+        }
+        AstNodeKind::Write { exprs, separator } => match separator {
+            Some(separator) if exprs.is_empty() => format!("printjoin({})", quote(separator)),
+            Some(separator) => format!("print({}, sep = {})", list(exprs), quote(separator)),
+            None => format!("print({})", list(exprs)),
+        },
+        AstNodeKind::Return(None) => "return".to_owned(),
+        AstNodeKind::Return(Some(value)) => match &value.kind {
+            AstNodeKind::Tuple(values) => format!("return {}", list(values)),
+            _ => format!("return {}", expression(value)),
+        },
+        AstNodeKind::Assert { expr, message } => match message {
+            Some(message) => format!("assert({}, {})", expression(expr), quote(message)),
+            None => format!("assert({})", expression(expr)),
+        },
+        AstNodeKind::Error(message) => format!("error({})", quote(message)),
+        AstNodeKind::Append { name, value } => format!("{name}.append({})", expression(value)),
+        AstNodeKind::Plot {
+            name,
+            column_1,
+            column_2,
+        } => format!(
+            "plot({name}, {}, {})",
+            expression(column_1),
+            expression(column_2)
+        ),
+        AstNodeKind::Histogram {
+            column,
+            name,
+            bins,
+            density,
+        } => {
+            let density = density
+                .as_ref()
+                .map(|density| format!(", {}", expression(density)))
+                .unwrap_or_default();
+            format!(
+                "histogram({name}, {}, {}{density})",
+                expression(column),
+                expression(bins)
+            )
+        }
+        AstNodeKind::Describe(name) => format!("describe({name})"),
+        AstNodeKind::Show { name, rows: None } => format!("show({name})"),
+        AstNodeKind::Show {
+            name,
+            rows: Some(rows),
+        } => format!("show({name}, {})", expression(rows)),
+        AstNodeKind::CastColumn {
+            name,
+            column,
+            data_type,
+        } => format!("cast_column({name}, {}, {data_type})", expression(column)),
+        _ => expression(node),
+    }
+}
+
+fn list(nodes: &[AstNode]) -> String {
+    let values: Vec<_> = nodes.iter().map(expression).collect();
+    values.join(", ")
+}
+
+fn expression(node: &AstNode) -> String {
+    match &node.kind {
+        AstNodeKind::Id(name) => name.clone(),
+        AstNodeKind::Integer(value) => value.to_string(),
+        AstNodeKind::Float(value) => {
+            let value = value.to_string();
+            match value.contains('.') {
+                true => value,
+                false => format!("{value}.0"),
+            }
+        }
+        AstNodeKind::String(value) => quote(value),
+        AstNodeKind::Bool(value) => value.to_string(),
+        AstNodeKind::Array(values) => format!("[{}]", list(values)),
+        AstNodeKind::ArrayDeclaration {
+            data_type,
+            dim1,
+            dim2,
+        } => {
+            let dim2 = dim2.map(|dim2| format!(", {dim2}")).unwrap_or_default();
+            format!("declare_arr<{data_type}>({dim1}{dim2})")
+        }
+        AstNodeKind::FilledArray { value, size } => format!("fill({}, {size})", expression(value)),
+        AstNodeKind::ArrayVal { name, idx_1, idx_2 } => {
+            let idx_2 = idx_2
+                .as_ref()
+                .map(|idx_2| format!("[{}]", expression(idx_2)))
+                .unwrap_or_default();
+            format!("{name}[{}]{idx_2}", expression(idx_1))
+        }
+        AstNodeKind::UnaryOperation {
+            operator: Operator::Not,
+            operand,
+        } => format!("NOT {}", operand_of(operand, NOT - 1)),
+        AstNodeKind::UnaryOperation { operator, operand } => {
+            format!("{}({})", operator.symbol(), expression(operand))
+        }
+        AstNodeKind::BinaryOperation { operator, lhs, rhs } => match precedence(node) {
+            ATOM => format!(
+                "{}({}, {})",
+                operator.symbol(),
+                expression(lhs),
+                expression(rhs)
+            ),
+            level => format!(
+                "{} {} {}",
+                operand_of(lhs, level),
+                operator.symbol(),
+                operand_of(rhs, level)
+            ),
+        },
+        AstNodeKind::Read(prompt) => format!("input({})", prompt_of(prompt)),
+        AstNodeKind::ReadBool(prompt) => format!("read_bool({})", prompt_of(prompt)),
+        AstNodeKind::Argv(index) => format!("argv({})", expression(index)),
+        AstNodeKind::Random => "random()".to_owned(),
+        AstNodeKind::RandomInt { low, high } => {
+            format!("random_int({}, {})", expression(low), expression(high))
+        }
+        AstNodeKind::FuncCall { name, exprs } => format!("{}({})", local_name(name), list(exprs)),
+        AstNodeKind::ReadCSV(file) => format!("read_csv({})", expression(file)),
+        AstNodeKind::ColumnsOf { name, size } => match *size {
+            COLUMNS_OF_SIZE => format!("columns_of({name})"),
+            size => format!("columns_of({name}, {size})"),
+        },
+        AstNodeKind::Split {
+            text,
+            separator,
+            size,
+        } => {
+            let size = match *size {
+                SPLIT_PARTS => String::new(),
+                size => format!(", {size}"),
+            };
+            format!(
+                "split({}, {}{size})",
+                expression(text),
+                expression(separator)
+            )
+        }
+        AstNodeKind::PureDataframeOp { name, operator } => format!("{}({name})", operator.symbol()),
+        AstNodeKind::UnaryDataframeOp {
+            column,
+            name,
+            operator,
+        } => format!("{}({name}, {})", operator.symbol(), expression(column)),
+        AstNodeKind::Correlation {
+            name,
+            column_1,
+            column_2,
+        } => format!(
+            "correlation({name}, {}, {})",
+            expression(column_1),
+            expression(column_2)
+        ),
+        AstNodeKind::Record { name, fields } if fields.is_empty() => format!("{name} {{}}"),
+        AstNodeKind::Record { name, fields } => {
+            let fields: Vec<_> = fields
+                .iter()
+                .map(|(field, value)| format!("{field}: {}", expression(value)))
+                .collect();
+            format!("{name} {{ {} }}", fields.join(", "))
+        }
+        node => unreachable!("{node:?} is not an expression"),
+    }
+}
+
+/// Wraps `node` in parentheses unless it binds tighter than `level`
+fn operand_of(node: &AstNode, level: u8) -> String {
+    match precedence(node) > level {
+        true => expression(node),
+        false => format!("({})", expression(node)),
+    }
+}
+
+/// How tightly the operator of `node` binds, following the grammar. Each
+/// level only allows one operator, so nesting the same level needs
+/// parentheses as well.
+fn precedence(node: &AstNode) -> u8 {
+    match &node.kind {
+        AstNodeKind::UnaryOperation {
+            operator: Operator::Not,
+            ..
+        } => NOT,
+        AstNodeKind::BinaryOperation { operator, .. } => match operator {
+            Operator::Iff => 1,
+            Operator::Implies => 2,
+            Operator::Or => 3,
+            Operator::And => 4,
+            Operator::Eq | Operator::Ne => 6,
+            Operator::Gte | Operator::Lte | Operator::Gt | Operator::Lt => 7,
+            Operator::Sum | Operator::Minus => 8,
+            Operator::Times | Operator::Div => 9,
+            _ => ATOM,
+        },
+        _ => ATOM,
+    }
+}
+
+fn prompt_of(prompt: &Option<String>) -> String {
+    prompt.as_deref().map(quote).unwrap_or_default()
+}
+
+/// Strings can't escape their delimiter, so single quotes are used for the
+/// ones containing double quotes
+fn quote(value: &str) -> String {
+    match value.contains('"') {
+        true => format!("'{value}'"),
+        false => format!("\"{value}\""),
+    }
+}
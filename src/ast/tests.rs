@@ -0,0 +1,52 @@
+use super::visitor::{walk, Visitor};
+use super::*;
+use crate::parser::parse;
+
+#[derive(Default)]
+struct Collector {
+    calls: Vec<String>,
+    ids: Vec<String>,
+}
+
+impl<'a> Visitor<'a> for Collector {
+    fn visit_id(&mut self, node: &AstNode<'a>) {
+        self.ids.push(String::from(node));
+    }
+
+    fn visit_func_call(&mut self, node: &AstNode<'a>) {
+        if let AstNodeKind::FuncCall { name, .. } = &node.kind {
+            self.calls.push(name.clone());
+        }
+        walk(self, node);
+    }
+}
+
+#[test]
+fn visitor_reaches_nested_nodes() {
+    let program = "
+        func f(x: int): int { return x; }
+        func main(): void {
+            a = 1;
+            for (i = 0 to 3) {
+                match i { 1 => print(f(a)); _ => { if (a > i) print(f(i)); } }
+            }
+        }
+    ";
+    let ast = parse(program, false).unwrap();
+    let mut collector = Collector::default();
+    collector.visit_node(&ast);
+    assert_eq!(collector.calls, vec!["f", "f"]);
+    assert_eq!(
+        collector.ids,
+        vec!["x", "a", "i", "i", "i", "a", "a", "i", "i"]
+    );
+}
+
+#[test]
+fn leaves_have_no_children() {
+    let ast = parse("func main(): void { print(1); }", false).unwrap();
+    let write = ast.children()[0];
+    let value = write.children()[0];
+    assert_eq!(value.kind, AstNodeKind::Integer(1));
+    assert!(value.children().is_empty());
+}
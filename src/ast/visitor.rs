@@ -0,0 +1,258 @@
+use super::{ast_kind::AstNodeKind, AstNode};
+
+/// Traverses the AST. Every `visit_*` method defaults to walking into the
+/// children of the node, so implementors only need to override the kinds
+/// they care about and call [`walk`] to keep going deeper.
+pub trait Visitor<'a> {
+    fn visit_node(&mut self, node: &AstNode<'a>) {
+        match &node.kind {
+            AstNodeKind::Id(_) => self.visit_id(node),
+            AstNodeKind::Integer(_) => self.visit_integer(node),
+            AstNodeKind::Float(_) => self.visit_float(node),
+            AstNodeKind::String(_) => self.visit_string(node),
+            AstNodeKind::Bool(_) => self.visit_bool(node),
+            AstNodeKind::Array(_) => self.visit_array(node),
+            AstNodeKind::ArrayDeclaration { .. } => self.visit_array_declaration(node),
+            AstNodeKind::FilledArray { .. } => self.visit_filled_array(node),
+            AstNodeKind::ArrayVal { .. } => self.visit_array_val(node),
+            AstNodeKind::Assignment { .. } => self.visit_assignment(node),
+            AstNodeKind::UnaryOperation { .. } => self.visit_unary_operation(node),
+            AstNodeKind::Increment { .. } => self.visit_increment(node),
+            AstNodeKind::BinaryOperation { .. } => self.visit_binary_operation(node),
+            AstNodeKind::Main { .. } => self.visit_main(node),
+            AstNodeKind::Argument { .. } => self.visit_argument(node),
+            AstNodeKind::Function { .. } => self.visit_function(node),
+            AstNodeKind::Write { .. } => self.visit_write(node),
+            AstNodeKind::Read(_) | AstNodeKind::ReadBool(_) => self.visit_read(node),
+            AstNodeKind::Decision { .. } => self.visit_decision(node),
+            AstNodeKind::ElseBlock(_) => self.visit_else_block(node),
+            AstNodeKind::While { .. } => self.visit_while(node),
+            AstNodeKind::Repeat { .. } => self.visit_repeat(node),
+            AstNodeKind::Try { .. } => self.visit_try(node),
+            AstNodeKind::For { .. } => self.visit_for(node),
+            AstNodeKind::ForEach { .. } => self.visit_for_each(node),
+            AstNodeKind::Match { .. } => self.visit_match(node),
+            AstNodeKind::FuncCall { .. } => self.visit_func_call(node),
+            AstNodeKind::Return(_) => self.visit_return(node),
+            AstNodeKind::Assert { .. } => self.visit_assert(node),
+            AstNodeKind::Error(_) => self.visit_error(node),
+            AstNodeKind::Append { .. } => self.visit_append(node),
+            AstNodeKind::Argv(_) => self.visit_argv(node),
+            AstNodeKind::Tuple(_) => self.visit_tuple(node),
+            AstNodeKind::MultiAssignment { .. } => self.visit_multi_assignment(node),
+            AstNodeKind::Random | AstNodeKind::RandomInt { .. } => self.visit_random(node),
+            AstNodeKind::ReadCSV(_) => self.visit_read_csv(node),
+            AstNodeKind::ColumnsOf { .. } => self.visit_columns_of(node),
+            AstNodeKind::Split { .. } => self.visit_split(node),
+            AstNodeKind::PureDataframeOp { .. } => self.visit_pure_dataframe_op(node),
+            AstNodeKind::UnaryDataframeOp { .. } => self.visit_unary_dataframe_op(node),
+            AstNodeKind::Correlation { .. } => self.visit_correlation(node),
+            AstNodeKind::Plot { .. } => self.visit_plot(node),
+            AstNodeKind::Histogram { .. } => self.visit_histogram(node),
+            AstNodeKind::Describe(_) => self.visit_describe(node),
+            AstNodeKind::Show { .. } => self.visit_show(node),
+            AstNodeKind::CastColumn { .. } => self.visit_cast_column(node),
+            AstNodeKind::RecordDeclaration { .. } => self.visit_record_declaration(node),
+            AstNodeKind::Record { .. } => self.visit_record(node),
+        }
+    }
+
+    fn visit_id(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_integer(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_float(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_string(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_bool(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_array(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_array_declaration(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_filled_array(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_array_val(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_assignment(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_unary_operation(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_increment(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_binary_operation(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_main(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_argument(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_function(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_write(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_read(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_decision(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_else_block(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_while(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_repeat(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_try(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_for(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_for_each(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_match(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_func_call(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_return(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_assert(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_error(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_append(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_argv(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_tuple(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_multi_assignment(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_random(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_read_csv(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_columns_of(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_split(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_pure_dataframe_op(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_unary_dataframe_op(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_correlation(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_plot(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_histogram(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_describe(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_show(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_cast_column(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_record_declaration(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+
+    fn visit_record(&mut self, node: &AstNode<'a>) {
+        walk(self, node);
+    }
+}
+
+/// Visits every child of the node, in source order
+pub fn walk<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, node: &AstNode<'a>) {
+    node.children()
+        .into_iter()
+        .for_each(|child| visitor.visit_node(child));
+}
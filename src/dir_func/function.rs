@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use serde::{Deserialize, Serialize};
+
 use crate::{
     address::{AddressManager, GenericAddressManager, TempAddressManager, TOTAL_SIZE},
     ast::ast_kind::AstNodeKind,
@@ -12,6 +14,9 @@ use crate::{
 use super::variable::{Dimensions, Variable};
 
 pub type VariablesTable = HashMap<String, Variable>;
+/// Fields of each declared record, in declaration order
+pub type RecordsTable = HashMap<String, Vec<(String, Types)>>;
+pub type FunctionKey = (String, usize);
 type InsertResult = std::result::Result<(), RaoulErrorKind>;
 
 pub trait Scope {
@@ -46,7 +51,7 @@ pub trait Scope {
     }
 }
 
-#[derive(PartialEq, Clone, Debug)]
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub struct Function {
     pub address: usize,
     pub args: Vec<Operand>,
@@ -54,6 +59,8 @@ pub struct Function {
     pub local_addresses: AddressManager,
     pub name: String,
     pub return_type: Types,
+    /// Global slots of the values returned after the first one
+    pub extra_returns: Vec<Operand>,
     pub temp_addresses: TempAddressManager,
     pub variables: VariablesTable,
 }
@@ -66,6 +73,7 @@ impl Function {
             local_addresses: AddressManager::new(TOTAL_SIZE),
             name,
             return_type,
+            extra_returns: Vec::new(),
             temp_addresses: TempAddressManager::new(),
             variables: HashMap::new(),
             first_quad: 0,
@@ -77,26 +85,37 @@ impl Function {
         node: &AstNode<'a>,
         global_fn: &mut GlobalScope,
         argument: bool,
+        fail_fast: bool,
     ) -> Results<'a, ()> {
-        match Variable::from_node(node, self, global_fn) {
-            Ok((variable, global)) => {
-                let address = variable.address;
-                let data_type = variable.data_type;
-                let result = if global {
-                    global_fn.insert_variable(variable)
-                } else {
-                    self.insert_variable(variable)
-                };
-                if let Err(kind) = result {
-                    return Err(RaoulError::new_vec(node, kind));
-                }
-                if argument {
-                    self.args.push((address, data_type));
-                }
-                Ok(())
+        let variables = match &node.kind {
+            AstNodeKind::Assignment { value, .. }
+                if matches!(value.kind, AstNodeKind::Record { .. }) =>
+            {
+                Variable::from_record(node, Some(self), global_fn)
+            }
+            AstNodeKind::MultiAssignment { .. } => {
+                Variable::from_multi_assignment(node, self, global_fn, fail_fast)
+            }
+            _ => {
+                Variable::from_node(node, self, global_fn, fail_fast).map(|variable| vec![variable])
+            }
+        }?;
+        for (variable, global) in variables {
+            let address = variable.address;
+            let data_type = variable.data_type;
+            let result = if global {
+                global_fn.insert_variable(variable)
+            } else {
+                self.insert_variable(variable)
+            };
+            if let Err(kind) = result {
+                return Err(RaoulError::new_vec(node, kind));
+            }
+            if argument {
+                self.args.push((address, data_type));
             }
-            Err(errors) => Err(errors),
         }
+        Ok(())
     }
 
     fn insert_from_nodes<'a>(
@@ -104,38 +123,94 @@ impl Function {
         nodes: &[AstNode<'a>],
         global_fn: &mut GlobalScope,
         is_arg: bool,
+        fail_fast: bool,
     ) -> Results<'a, ()> {
         RaoulError::create_results(
+            fail_fast,
             nodes
                 .iter()
                 .flat_map(AstNode::expand_node)
                 .filter(AstNode::is_declaration)
-                .map(|node| self.insert_variable_from_node(&node, global_fn, is_arg)),
+                .map(|node| self.insert_variable_from_node(&node, global_fn, is_arg, fail_fast)),
         )
     }
 
-    pub fn try_create<'a>(v: &AstNode<'a>, global_fn: &mut GlobalScope) -> Results<'a, Function> {
+    pub fn try_create<'a>(
+        v: &AstNode<'a>,
+        global_fn: &mut GlobalScope,
+        fail_fast: bool,
+    ) -> Results<'a, Function> {
         match v.kind.clone() {
             AstNodeKind::Function {
                 name,
                 return_type,
+                ref extra_returns,
                 ref body,
                 ref arguments,
             } => {
                 let mut function = Function::new(name, return_type);
-                function.insert_from_nodes(arguments, global_fn, true)?;
-                function.insert_from_nodes(body, global_fn, false)?;
+                function.extra_returns = extra_returns
+                    .iter()
+                    .map(|data_type| (usize::MAX, *data_type))
+                    .collect();
+                function.insert_from_nodes(arguments, global_fn, true, fail_fast)?;
+                // Registered before the body so it can call the function itself
+                if let Err(kind) = function.insert_return_slots(global_fn) {
+                    return Err(RaoulError::new_vec(v, kind));
+                }
+                function.insert_from_nodes(body, global_fn, false, fail_fast)?;
                 Ok(function)
             }
-            AstNodeKind::Main { ref body, .. } => {
+            AstNodeKind::Main {
+                body: Some(ref body),
+                ..
+            } => {
                 let mut function = Function::new("main".to_string(), Types::Void);
-                function.insert_from_nodes(body, global_fn, false)?;
+                function.insert_from_nodes(body, global_fn, false, fail_fast)?;
                 Ok(function)
             }
             _ => unreachable!(),
         }
     }
 
+    /// Gives an address to the global slots of each returned value
+    fn insert_return_slots(&mut self, global_fn: &mut GlobalScope) -> InsertResult {
+        let memory_exceded = |data_type| RaoulErrorKind::MemoryExceded {
+            data_type,
+            requested: 1,
+        };
+        if self.return_type != Types::Void {
+            let address = global_fn
+                .addresses
+                .get_address(self.return_type, (None, None))
+                .ok_or_else(|| memory_exceded(self.return_type))?;
+            global_fn.insert_variable(Variable::from_function(self, address))?;
+            self.address = address;
+        }
+        for index in 1..=self.extra_returns.len() {
+            let data_type = self.extra_returns[index - 1].1;
+            let address = global_fn
+                .addresses
+                .get_address(data_type, (None, None))
+                .ok_or_else(|| memory_exceded(data_type))?;
+            global_fn.insert_variable(Variable::from_extra_return(self, index, address))?;
+            self.extra_returns[index - 1].0 = address;
+        }
+        Ok(())
+    }
+
+    pub fn key(&self) -> FunctionKey {
+        (self.name.clone(), self.args.len())
+    }
+
+    /// Amount of values the function returns
+    pub fn returns_amount(&self) -> usize {
+        match self.return_type {
+            Types::Void => 0,
+            _ => 1 + self.extra_returns.len(),
+        }
+    }
+
     pub fn size(&self) -> usize {
         self.local_addresses.size() + self.temp_addresses.size()
     }
@@ -159,26 +234,61 @@ impl Scope for Function {
 
 #[derive(PartialEq, Clone, Debug)]
 pub struct GlobalScope {
-    has_dataframe: bool,
+    /// Variable holding the only dataframe of the program
+    dataframe: Option<String>,
     pub addresses: AddressManager,
+    pub records: RecordsTable,
     pub variables: VariablesTable,
+    /// Return type of every declared function, as void ones have no return
+    /// variable
+    functions: HashMap<FunctionKey, Types>,
 }
 
 impl GlobalScope {
     pub fn new() -> Self {
         Self {
             addresses: AddressManager::new(0),
+            records: HashMap::new(),
             variables: HashMap::new(),
-            has_dataframe: false,
+            dataframe: None,
+            functions: HashMap::new(),
         }
     }
 
-    pub fn add_dataframe(&mut self) -> bool {
-        if self.has_dataframe {
-            false
-        } else {
-            self.has_dataframe = true;
-            true
+    pub fn add_function(&mut self, key: FunctionKey, return_type: Types) {
+        self.functions.insert(key, return_type);
+    }
+
+    /// Error for a call whose value is used but that has no return variable:
+    /// the function is void, the name belongs to a variable or it doesn't
+    /// exist at all
+    pub fn missing_return_kind(
+        &self,
+        name: &str,
+        arity: usize,
+        variables: &VariablesTable,
+    ) -> RaoulErrorKind {
+        let key = (name.to_owned(), arity);
+        if self.functions.get(&key) == Some(&Types::Void) {
+            return RaoulErrorKind::VoidFunction(name.to_owned());
+        }
+        let is_function = self.functions.keys().any(|(function, _)| function == name);
+        let is_variable = variables.contains_key(name) || self.variables.contains_key(name);
+        if is_variable && !is_function {
+            return RaoulErrorKind::NotCallable(name.to_owned());
+        }
+        RaoulErrorKind::UndeclaredFunction(name.to_owned())
+    }
+
+    /// Whether `name` can hold the dataframe, which is only possible for the
+    /// first variable that held one, so a file can be loaded into it again
+    pub fn add_dataframe(&mut self, name: &str) -> bool {
+        match &self.dataframe {
+            Some(dataframe) => dataframe == name,
+            None => {
+                self.dataframe = Some(name.to_owned());
+                true
+            }
         }
     }
 }
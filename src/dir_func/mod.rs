@@ -1,15 +1,15 @@
 use std::collections::HashMap;
 
 use crate::{
-    address::GenericAddressManager,
     ast::ast_kind::AstNodeKind,
     ast::AstNode,
     enums::Types,
     error::{error_kind::RaoulErrorKind, RaoulError, Result, Results},
+    quadruple::operand::QuadOperand,
 };
 
 use self::{
-    function::{Function, GlobalScope, Scope},
+    function::{Function, FunctionKey, GlobalScope, Scope, VariablesTable},
     variable::Variable,
 };
 
@@ -17,7 +17,7 @@ pub mod function;
 pub mod variable;
 pub mod variable_value;
 
-pub type FunctionTable = HashMap<String, Function>;
+pub type FunctionTable = HashMap<FunctionKey, Function>;
 
 #[derive(PartialEq, Debug, Clone)]
 pub struct DirFunc {
@@ -40,71 +40,227 @@ impl DirFunc {
             .for_each(|f| f.variables.clear());
     }
 
+    /// Table of the global variables and the ones of each function, sorted by
+    /// address. Must be called before `clear_variables`
+    pub fn symbols(&self) -> String {
+        let mut sections = vec![format!(
+            "global\n{}",
+            symbols_table(&self.global_fn.variables)
+        )];
+        let mut functions: Vec<_> = self.functions.values().collect();
+        functions.sort_by_key(|function| function.key());
+        for function in functions {
+            let args: Vec<_> = function
+                .args
+                .iter()
+                .map(|(_, data_type)| data_type.to_string())
+                .collect();
+            let mut return_types = vec![function.return_type.to_string()];
+            return_types.extend(
+                function
+                    .extra_returns
+                    .iter()
+                    .map(|(_, data_type)| data_type.to_string()),
+            );
+            let return_type = match return_types.len() {
+                1 => return_types[0].to_owned(),
+                _ => format!("({})", return_types.join(", ")),
+            };
+            sections.push(format!(
+                "func {}({}): {return_type}\n{}",
+                function.name,
+                args.join(", "),
+                symbols_table(&function.variables)
+            ));
+        }
+        sections.join("\n")
+    }
+
     fn insert_function<'a>(&mut self, function: Function, node: &AstNode<'a>) -> Result<'a, ()> {
-        let name = function.name.clone();
-        match self.functions.get(&name) {
-            Some(_) => Err(RaoulError::new(
+        let key = function.key();
+        if self.functions.contains_key(&key) {
+            return Err(RaoulError::new(
                 node,
-                RaoulErrorKind::RedeclaredFunction(name),
-            )),
-            None => {
-                self.functions.insert(name, function);
+                RaoulErrorKind::RedeclaredFunction(key.0),
+            ));
+        }
+        self.global_fn
+            .add_function(key.clone(), function.return_type);
+        self.functions.insert(key, function);
+        Ok(())
+    }
+
+    fn insert_record<'a>(&mut self, node: &AstNode<'a>) -> Results<'a, ()> {
+        match &node.kind {
+            AstNodeKind::RecordDeclaration { name, fields } => {
+                if self.global_fn.records.contains_key(name) {
+                    let kind = RaoulErrorKind::RedeclaredRecord(name.clone());
+                    return Err(RaoulError::new_vec(node, kind));
+                }
+                self.global_fn.records.insert(name.clone(), fields.clone());
                 Ok(())
             }
+            _ => unreachable!(),
         }
     }
 
-    fn insert_function_from_node<'a>(&mut self, node: &AstNode<'a>) -> Results<'a, ()> {
-        let mut function = Function::try_create(node, &mut self.global_fn)?;
-        if function.return_type != Types::Void {
-            let address = self
-                .global_fn
-                .addresses
-                .get_address(function.return_type, (None, None));
-            match address {
-                Some(address) => {
-                    let result = self
-                        .global_fn
-                        .insert_variable(Variable::from_function(&function, address));
-                    if let Err(kind) = result {
-                        return Err(vec![RaoulError::new(node, kind)]);
-                    }
-                    function.address = address;
-                }
-                None => {
-                    let kind = RaoulErrorKind::MemoryExceded;
-                    return Err(vec![RaoulError::new(node, kind)]);
-                }
+    /// Variables declared by a global assignment, one per field when a
+    /// record is assigned
+    fn global_variables<'a>(
+        &mut self,
+        node: &AstNode<'a>,
+        fail_fast: bool,
+    ) -> Results<'a, Vec<Variable>> {
+        match &node.kind {
+            AstNodeKind::Assignment { value, .. }
+                if matches!(value.kind, AstNodeKind::Record { .. }) =>
+            {
+                let variables = Variable::from_record(node, None, &mut self.global_fn)?;
+                Ok(variables
+                    .into_iter()
+                    .map(|(variable, _)| variable)
+                    .collect())
             }
+            _ => Ok(vec![Variable::from_global(
+                node,
+                &mut self.global_fn,
+                fail_fast,
+            )?]),
         }
+    }
+
+    /// Arities of every function declared with the given name, sorted
+    pub fn function_arities(&self, name: &str) -> Vec<usize> {
+        let mut arities: Vec<_> = self
+            .functions
+            .keys()
+            .filter(|(function_name, _)| function_name == name)
+            .map(|(_, arity)| *arity)
+            .collect();
+        arities.sort_unstable();
+        arities
+    }
+
+    fn insert_function_from_node<'a>(
+        &mut self,
+        node: &AstNode<'a>,
+        fail_fast: bool,
+    ) -> Results<'a, ()> {
+        let function = Function::try_create(node, &mut self.global_fn, fail_fast)?;
         match self.insert_function(function, node) {
             Ok(_) => Ok(()),
             Err(error) => Err(vec![error]),
         }
     }
 
-    pub fn build_dir_func<'a>(&mut self, node: &AstNode<'a>) -> Results<'a, ()> {
+    /// Registers the variables declared with the `global` prefix inside the
+    /// functions before any function is built, so a function can use them no
+    /// matter where they are declared. The ones that can't be typed yet are
+    /// left for the regular pass, which reports their errors.
+    fn hoist_globals<'a>(
+        &mut self,
+        functions: &[AstNode<'a>],
+        main: &AstNode<'a>,
+        fail_fast: bool,
+    ) {
+        let assignments = functions
+            .iter()
+            .chain(Some(main))
+            .flat_map(|function| match &function.kind {
+                AstNodeKind::Function { body, .. }
+                | AstNodeKind::Main {
+                    body: Some(body), ..
+                } => body.iter().flat_map(AstNode::expand_node).collect(),
+                _ => vec![],
+            })
+            .filter(|node| matches!(node.kind, AstNodeKind::Assignment { global: true, .. }));
+        for assignment in assignments {
+            if let AstNodeKind::Assignment { value, .. } = &assignment.kind {
+                let globals = &self.global_fn.variables;
+                // Dataframes are counted when declared, so they can't be hoisted.
+                // Records aren't values, their fields are typed as declared
+                if !matches!(value.kind, AstNodeKind::Record { .. }) {
+                    match Types::from_node(value, globals, &self.global_fn, fail_fast) {
+                        Ok(Types::Dataframe) | Err(_) => continue,
+                        Ok(_) => (),
+                    }
+                }
+            }
+            for variable in self
+                .global_variables(&assignment, fail_fast)
+                .unwrap_or_default()
+            {
+                self.global_fn.insert_variable(variable).ok();
+            }
+        }
+    }
+
+    /// Fills the directory with the records, globals and functions of the
+    /// program, stopping at the first error when `fail_fast` is set
+    pub fn build_dir_func<'a>(&mut self, node: &AstNode<'a>, fail_fast: bool) -> Results<'a, ()> {
         match &node.kind {
+            AstNodeKind::Main { body: None, .. } => {
+                Err(RaoulError::new_vec(node, RaoulErrorKind::MissingMain))
+            }
             AstNodeKind::Main {
                 functions,
                 assignments,
+                records,
                 ..
             } => {
-                RaoulError::create_results(assignments.iter().map(|node| -> Results<()> {
-                    let variable = Variable::from_global(node, &mut self.global_fn)?;
-                    match self.global_fn.insert_variable(variable) {
-                        Ok(_) => Ok(()),
-                        Err(kind) => Err(RaoulError::new_vec(node, kind)),
-                    }
-                }))?;
                 RaoulError::create_results(
+                    fail_fast,
+                    records.iter().map(|node| self.insert_record(node)),
+                )?;
+                RaoulError::create_results(
+                    fail_fast,
+                    assignments.iter().map(|node| -> Results<()> {
+                        for variable in self.global_variables(node, fail_fast)? {
+                            if let Err(kind) = self.global_fn.insert_variable(variable) {
+                                return Err(RaoulError::new_vec(node, kind));
+                            }
+                        }
+                        Ok(())
+                    }),
+                )?;
+                self.hoist_globals(functions, node, fail_fast);
+                RaoulError::create_results(
+                    fail_fast,
                     functions
                         .iter()
                         .chain(Some(node))
-                        .map(|node| self.insert_function_from_node(node)),
+                        .map(|node| self.insert_function_from_node(node, fail_fast)),
                 )
             }
             _ => unreachable!(),
         }
     }
 }
+
+impl Default for DirFunc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn symbols_table(variables: &VariablesTable) -> String {
+    let mut variables: Vec<_> = variables.values().collect();
+    variables.sort_by_key(|variable| variable.address);
+    let width = variables.iter().map(|v| v.name.len()).max().unwrap_or(0);
+    variables
+        .into_iter()
+        .map(|variable| {
+            let dimensions: String = [variable.dimensions.0, variable.dimensions.1]
+                .into_iter()
+                .flatten()
+                .map(|size| format!("[{size}]"))
+                .collect();
+            format!(
+                "  {:<7?} {:<width$} {}{dimensions}\n",
+                QuadOperand::Address(variable.address),
+                variable.name,
+                variable.data_type
+            )
+        })
+        .collect()
+}
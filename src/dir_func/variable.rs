@@ -1,5 +1,5 @@
 use crate::{
-    address::GenericAddressManager,
+    address::{get_amount, GenericAddressManager},
     ast::ast_kind::AstNodeKind,
     ast::AstNode,
     enums::Types,
@@ -7,16 +7,35 @@ use crate::{
     error::{RaoulError, Results},
 };
 
-use super::function::{Function, GlobalScope, Scope};
+use serde::{Deserialize, Serialize};
+
+use super::function::{Function, GlobalScope, Scope, VariablesTable};
 
 pub type Dimensions = (Option<usize>, Option<usize>);
 
-#[derive(Clone, PartialEq, Debug)]
+/// Name of the global variable holding the return value of a function. The
+/// arity is part of it so overloaded functions don't share the same slot.
+pub fn return_variable_name(name: &str, arity: usize) -> String {
+    format!("{name}/{arity}")
+}
+
+/// Name of the global variable holding the `index`-th value returned by a
+/// function, the first one being its regular return value
+pub fn return_slot_name(name: &str, arity: usize, index: usize) -> String {
+    match index {
+        0 => return_variable_name(name, arity),
+        _ => format!("{}#{index}", return_variable_name(name, arity)),
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub struct Variable {
     pub address: usize,
     pub data_type: Types,
     pub dimensions: Dimensions,
     pub name: String,
+    /// Struct of the record the variable is a field of
+    pub record: Option<String>,
 }
 
 fn get_value_dimensions<'a>(value: &AstNode<'a>, node: &AstNode<'a>) -> Results<'a, Dimensions> {
@@ -29,15 +48,179 @@ fn get_value_dimensions<'a>(value: &AstNode<'a>, node: &AstNode<'a>) -> Results<
     }
 }
 
+/// Struct of the record called `name`, known by any of its fields
+fn record_type<'v>(
+    name: &str,
+    tables: impl IntoIterator<Item = &'v VariablesTable>,
+) -> Option<String> {
+    let prefix = format!("{name}.");
+    tables
+        .into_iter()
+        .flat_map(|variables| variables.values())
+        .find(|variable| variable.name.starts_with(&prefix))
+        .and_then(|variable| variable.record.clone())
+}
+
+/// Error for using a variable that wasn't declared, which for the field of
+/// a record tells the struct doesn't have it, and for a record tells it can
+/// only be used through its fields
+pub fn undeclared_kind<'v>(
+    name: &str,
+    tables: impl IntoIterator<Item = &'v VariablesTable> + Clone,
+) -> RaoulErrorKind {
+    if let Some(record) = record_type(name, tables.clone()) {
+        return RaoulErrorKind::WholeRecord {
+            name: name.to_owned(),
+            record,
+        };
+    }
+    let record = name
+        .split_once('.')
+        .and_then(|(record, field)| Some((record_type(record, tables)?, field)));
+    match record {
+        Some((record, field)) => RaoulErrorKind::UnknownField {
+            record,
+            field: field.to_owned(),
+        },
+        None => RaoulErrorKind::UndeclaredVar(name.to_owned()),
+    }
+}
+
+fn scope_tables<'v>(
+    current_fn: Option<&'v Function>,
+    global_fn: &'v GlobalScope,
+) -> impl Iterator<Item = &'v VariablesTable> + Clone {
+    current_fn
+        .map(|function| &function.variables)
+        .into_iter()
+        .chain(Some(&global_fn.variables))
+}
+
+/// Fields are only created along with their record, so assigning to one
+/// that doesn't exist is an error instead of a new variable
+fn assert_field_exists<'a>(
+    name: &str,
+    current_fn: Option<&Function>,
+    global_fn: &GlobalScope,
+    node: &AstNode<'a>,
+) -> Results<'a, ()> {
+    let declared = current_fn
+        .and_then(|function| function.get_variable(name))
+        .is_some()
+        || global_fn.get_variable(name).is_some();
+    if !name.contains('.') || declared {
+        return Ok(());
+    }
+    let kind = undeclared_kind(name, scope_tables(current_fn, global_fn));
+    Err(RaoulError::new_vec(node, kind))
+}
+
+/// A record keeps the struct it was built with, so it can't be assigned a
+/// value of any other type
+fn assert_not_record<'a>(
+    name: &str,
+    data_type: Types,
+    current_fn: Option<&Function>,
+    global_fn: &GlobalScope,
+    node: &AstNode<'a>,
+) -> Results<'a, ()> {
+    match record_type(name, scope_tables(current_fn, global_fn)) {
+        Some(record) => {
+            let kind = RaoulErrorKind::MismatchedRecord {
+                name: name.to_owned(),
+                expected: record,
+                given: data_type.to_string(),
+            };
+            Err(RaoulError::new_vec(node, kind))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Like `assert_not_record`, for assigning a record of `struct_name`
+fn assert_same_record<'a>(
+    name: &str,
+    struct_name: &str,
+    current_fn: Option<&Function>,
+    global_fn: &GlobalScope,
+    node: &AstNode<'a>,
+) -> Results<'a, ()> {
+    let expected = match current_fn
+        .and_then(|function| function.get_variable(name))
+        .or_else(|| global_fn.get_variable(name))
+    {
+        Some(variable) => Some(variable.data_type.to_string()),
+        None => record_type(name, scope_tables(current_fn, global_fn)),
+    };
+    match expected {
+        Some(expected) if expected != struct_name => {
+            let kind = RaoulErrorKind::MismatchedRecord {
+                name: name.to_owned(),
+                expected,
+                given: struct_name.to_owned(),
+            };
+            Err(RaoulError::new_vec(node, kind))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Declared fields of the record built by `value`, after checking that every
+/// one of them, and only them, is given a value
+fn record_fields<'a>(
+    value: &AstNode<'a>,
+    global_fn: &GlobalScope,
+) -> Results<'a, Vec<(String, Types)>> {
+    match &value.kind {
+        AstNodeKind::Record { name, fields } => {
+            let declared = match global_fn.records.get(name) {
+                Some(declared) => declared,
+                None => {
+                    let kind = RaoulErrorKind::UndeclaredRecord(name.clone());
+                    return Err(RaoulError::new_vec(value, kind));
+                }
+            };
+            let unknown = fields
+                .iter()
+                .filter(|(field, _)| !declared.iter().any(|(name, _)| name == field))
+                .map(|(field, node)| {
+                    let kind = RaoulErrorKind::UnknownField {
+                        record: name.clone(),
+                        field: field.clone(),
+                    };
+                    RaoulError::new(node, kind)
+                });
+            let missing = declared
+                .iter()
+                .filter(|(field, _)| !fields.iter().any(|(name, _)| name == field))
+                .map(|(field, _)| {
+                    let kind = RaoulErrorKind::MissingField {
+                        record: name.clone(),
+                        field: field.clone(),
+                    };
+                    RaoulError::new(value, kind)
+                });
+            let errors: Vec<_> = unknown.chain(missing).collect();
+            if errors.is_empty() {
+                Ok(declared.clone())
+            } else {
+                Err(errors)
+            }
+        }
+        kind => unreachable!("{kind:?}"),
+    }
+}
+
 fn assert_dataframe<'a>(
     data_type: Types,
+    name: &str,
     global_fn: &mut GlobalScope,
     node: &AstNode<'a>,
 ) -> Results<'a, ()> {
     if data_type != Types::Dataframe {
         return Ok(());
     }
-    if global_fn.add_dataframe() {
+    if global_fn.add_dataframe(name) {
         Ok(())
     } else {
         Err(RaoulError::new_vec(node, RaoulErrorKind::OnlyOneDataframe))
@@ -45,24 +228,37 @@ fn assert_dataframe<'a>(
 }
 
 impl Variable {
-    pub fn from_global<'a>(v: &AstNode<'a>, global_fn: &mut GlobalScope) -> Results<'a, Variable> {
+    pub fn from_global<'a>(
+        v: &AstNode<'a>,
+        global_fn: &mut GlobalScope,
+        fail_fast: bool,
+    ) -> Results<'a, Variable> {
         match &v.kind {
             AstNodeKind::Assignment {
                 assignee, value, ..
             } => {
                 let data_type =
-                    Types::from_node(&*value, &global_fn.variables, &global_fn.variables)?;
-                assert_dataframe(data_type, global_fn, v)?;
-                let dimensions = get_value_dimensions(value, v)?;
+                    Types::from_node(value, &global_fn.variables, global_fn, fail_fast)?;
                 let name: String = assignee.into();
+                assert_dataframe(data_type, &name, global_fn, v)?;
+                let dimensions = get_value_dimensions(value, v)?;
+                assert_field_exists(&name, None, global_fn, v)?;
+                assert_not_record(&name, data_type, None, global_fn, v)?;
                 match global_fn.get_variable_address(&name, data_type, dimensions) {
                     Some(address) => Ok(Variable {
                         address,
                         data_type,
                         dimensions,
                         name,
+                        record: None,
                     }),
-                    None => Err(RaoulError::new_vec(v, RaoulErrorKind::MemoryExceded)),
+                    None => {
+                        let kind = RaoulErrorKind::MemoryExceded {
+                            data_type,
+                            requested: get_amount(dimensions),
+                        };
+                        Err(RaoulError::new_vec(v, kind))
+                    }
                 }
             }
             kind => unreachable!("{kind:?}"),
@@ -73,6 +269,7 @@ impl Variable {
         v: &AstNode<'a>,
         current_fn: &mut Function,
         global_fn: &mut GlobalScope,
+        fail_fast: bool,
     ) -> Results<'a, (Variable, bool)> {
         match v.kind.clone() {
             AstNodeKind::Assignment {
@@ -81,10 +278,12 @@ impl Variable {
                 global,
             } => {
                 let data_type =
-                    Types::from_node(&*value, &current_fn.variables, &global_fn.variables)?;
-                assert_dataframe(data_type, global_fn, v)?;
-                let dimensions = get_value_dimensions(&value, v)?;
+                    Types::from_node(&value, &current_fn.variables, global_fn, fail_fast)?;
                 let name: String = assignee.into();
+                assert_dataframe(data_type, &name, global_fn, v)?;
+                let dimensions = get_value_dimensions(&value, v)?;
+                assert_field_exists(&name, Some(current_fn), global_fn, v)?;
+                assert_not_record(&name, data_type, Some(current_fn), global_fn, v)?;
                 let address = if global {
                     global_fn.get_variable_address(&name, data_type, dimensions)
                 } else {
@@ -97,10 +296,17 @@ impl Variable {
                             data_type,
                             dimensions,
                             name,
+                            record: None,
                         },
                         global,
                     )),
-                    None => Err(RaoulError::new_vec(v, RaoulErrorKind::MemoryExceded)),
+                    None => {
+                        let kind = RaoulErrorKind::MemoryExceded {
+                            data_type,
+                            requested: get_amount(dimensions),
+                        };
+                        Err(RaoulError::new_vec(v, kind))
+                    }
                 }
             }
             AstNodeKind::Argument {
@@ -117,11 +323,15 @@ impl Variable {
                             data_type,
                             name,
                             dimensions: (None, None),
+                            record: None,
                         },
                         false,
                     )),
                     None => {
-                        let kind = RaoulErrorKind::MemoryExceded;
+                        let kind = RaoulErrorKind::MemoryExceded {
+                            data_type,
+                            requested: 1,
+                        };
                         Err(RaoulError::new_vec(v, kind))
                     }
                 }
@@ -130,12 +340,133 @@ impl Variable {
         }
     }
 
+    /// Variables for the fields of the record assigned in `v`, named
+    /// `{record}.{field}` and typed as declared. Without a function they are
+    /// always globals.
+    pub fn from_record<'a>(
+        v: &AstNode<'a>,
+        mut current_fn: Option<&mut Function>,
+        global_fn: &mut GlobalScope,
+    ) -> Results<'a, Vec<(Variable, bool)>> {
+        match &v.kind {
+            AstNodeKind::Assignment {
+                assignee,
+                value,
+                global,
+            } => {
+                let declared = record_fields(value, global_fn)?;
+                let global = *global || current_fn.is_none();
+                let record: String = assignee.into();
+                let struct_name = match &value.kind {
+                    AstNodeKind::Record { name, .. } => name,
+                    kind => unreachable!("{kind:?}"),
+                };
+                assert_same_record(&record, struct_name, current_fn.as_deref(), global_fn, v)?;
+                let mut variables = Vec::new();
+                for (field, data_type) in declared {
+                    let name = format!("{record}.{field}");
+                    let address = match current_fn.as_mut() {
+                        Some(function) if !global => {
+                            function.get_variable_address(&name, data_type, (None, None))
+                        }
+                        _ => global_fn.get_variable_address(&name, data_type, (None, None)),
+                    };
+                    match address {
+                        Some(address) => variables.push((
+                            Variable {
+                                address,
+                                data_type,
+                                dimensions: (None, None),
+                                name,
+                                record: Some(struct_name.clone()),
+                            },
+                            global,
+                        )),
+                        None => {
+                            let kind = RaoulErrorKind::MemoryExceded {
+                                data_type,
+                                requested: 1,
+                            };
+                            return Err(RaoulError::new_vec(v, kind));
+                        }
+                    }
+                }
+                Ok(variables)
+            }
+            kind => unreachable!("{kind:?}"),
+        }
+    }
+
+    /// Variables assigned with the values returned by the call in `v`, each
+    /// one typed as the value it receives
+    pub fn from_multi_assignment<'a>(
+        v: &AstNode<'a>,
+        current_fn: &mut Function,
+        global_fn: &mut GlobalScope,
+        fail_fast: bool,
+    ) -> Results<'a, Vec<(Variable, bool)>> {
+        match &v.kind {
+            AstNodeKind::MultiAssignment { assignees, call } => {
+                let (name, arity) = match &call.kind {
+                    AstNodeKind::FuncCall { name, exprs } => (name, exprs.len()),
+                    kind => unreachable!("{kind:?}"),
+                };
+                let expected = (0..)
+                    .take_while(|&i| {
+                        let slot = return_slot_name(name, arity, i);
+                        global_fn.get_variable(&slot).is_some()
+                    })
+                    .count();
+                if expected == 0 {
+                    let kind = global_fn.missing_return_kind(name, arity, &current_fn.variables);
+                    return Err(RaoulError::new_vec(call, kind));
+                }
+                if expected != assignees.len() {
+                    let kind = RaoulErrorKind::UnmatchReturnsAmount {
+                        name: name.clone(),
+                        expected,
+                        given: assignees.len(),
+                    };
+                    return Err(RaoulError::new_vec(v, kind));
+                }
+                RaoulError::create_partition(
+                    fail_fast,
+                    assignees.iter().enumerate().map(|(i, assignee)| {
+                        let slot = return_slot_name(name, arity, i);
+                        let assignment = AstNode::new(
+                            AstNodeKind::Assignment {
+                                assignee: Box::new(assignee.clone()),
+                                value: Box::new(AstNode::new(AstNodeKind::Id(slot), &call.span)),
+                                global: false,
+                            },
+                            &assignee.span,
+                        );
+                        Variable::from_node(&assignment, current_fn, global_fn, fail_fast)
+                    }),
+                )
+            }
+            kind => unreachable!("{kind:?}"),
+        }
+    }
+
     pub fn from_function(function: &Function, address: usize) -> Self {
         Variable {
             address,
             data_type: function.return_type,
-            name: function.name.clone(),
+            name: return_variable_name(&function.name, function.args.len()),
+            dimensions: (None, None),
+            record: None,
+        }
+    }
+
+    /// Slot of the `index`-th value returned by the function, counting from 1
+    pub fn from_extra_return(function: &Function, index: usize, address: usize) -> Self {
+        Variable {
+            address,
+            data_type: function.extra_returns[index - 1].1,
+            name: return_slot_name(&function.name, function.args.len(), index),
             dimensions: (None, None),
+            record: None,
         }
     }
 }
@@ -2,10 +2,12 @@ use std::fmt;
 use std::io::stdin;
 use std::ops::{Add, BitAnd, BitOr, Div, Mul, Not, Sub};
 
+use serde::{Deserialize, Serialize};
+
 use crate::vm::VMResult;
 use crate::{ast::ast_kind::AstNodeKind, enums::Types};
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub enum VariableValue {
     Integer(i64),
     Float(f64),
@@ -64,6 +66,60 @@ impl VariableValue {
             }
         }
     }
+
+    pub fn decrease(&self) -> VMResult<Self> {
+        match self {
+            Self::Integer(v) => Ok(Self::Integer(v - 1)),
+            v => {
+                if v.is_number() {
+                    self.cast_to_float()? - Self::Float(1.0)
+                } else {
+                    unreachable!()
+                }
+            }
+        }
+    }
+
+    fn float_operation<F>(&self, f: F) -> VMResult<Self>
+    where
+        F: FnOnce(f64) -> f64,
+    {
+        match self {
+            Self::Bool(_) => Err("Expected a number but received a bool".to_owned()),
+            v => Ok(Self::Float(f(f64::try_from(v)?))),
+        }
+    }
+
+    pub fn abs(&self) -> VMResult<Self> {
+        match self {
+            Self::Integer(v) => match v.checked_abs() {
+                Some(v) => Ok(Self::Integer(v)),
+                None => Err("Integer overflow".to_owned()),
+            },
+            v => v.float_operation(f64::abs),
+        }
+    }
+
+    pub fn floor(&self) -> VMResult<Self> {
+        match self {
+            Self::Integer(v) => Ok(Self::Integer(*v)),
+            v => v.float_operation(f64::floor),
+        }
+    }
+
+    pub fn ceil(&self) -> VMResult<Self> {
+        match self {
+            Self::Integer(v) => Ok(Self::Integer(*v)),
+            v => v.float_operation(f64::ceil),
+        }
+    }
+
+    pub fn round(&self) -> VMResult<Self> {
+        match self {
+            Self::Integer(v) => Ok(Self::Integer(*v)),
+            v => v.float_operation(f64::round),
+        }
+    }
 }
 
 impl From<&VariableValue> for Types {
@@ -96,7 +152,7 @@ impl From<AstNodeKind<'_>> for VariableValue {
 }
 
 impl TryFrom<&VariableValue> for f64 {
-    type Error = &'static str;
+    type Error = String;
 
     fn try_from(v: &VariableValue) -> VMResult<Self> {
         if let VariableValue::Float(a) = v {
@@ -111,14 +167,14 @@ impl TryFrom<&VariableValue> for f64 {
             Ok(a) => Ok(a),
             Err(_) => {
                 println!("Given: {string}");
-                Err("Could not parse to float")
+                Err("Could not parse to float".to_owned())
             }
         }
     }
 }
 
 impl TryFrom<VariableValue> for f64 {
-    type Error = &'static str;
+    type Error = String;
 
     fn try_from(v: VariableValue) -> VMResult<Self> {
         Self::try_from(&v)
@@ -172,7 +228,7 @@ impl From<VariableValue> for String {
 }
 
 impl TryFrom<VariableValue> for i64 {
-    type Error = &'static str;
+    type Error = String;
 
     fn try_from(v: VariableValue) -> VMResult<Self> {
         if let VariableValue::Integer(a) = &v {
@@ -193,14 +249,14 @@ impl TryFrom<VariableValue> for i64 {
             Ok(a) => Ok(a),
             Err(_) => {
                 println!("Given: {string}");
-                Err("Could not parse to int")
+                Err("Could not parse to int".to_owned())
             }
         }
     }
 }
 
 impl TryFrom<&VariableValue> for i64 {
-    type Error = &'static str;
+    type Error = String;
 
     fn try_from(v: &VariableValue) -> VMResult<Self> {
         Self::try_from(v.clone())
@@ -261,20 +317,36 @@ impl Div for VariableValue {
     fn div(self, other: Self) -> Self::Output {
         if let (Self::Integer(a), Self::Integer(b)) = (self.clone(), other.clone()) {
             match b {
-                0 => Err("Attempt to divide by zero"),
+                0 => Err("Attempt to divide by zero".to_owned()),
                 b => Ok(Self::Integer(a / b)),
             }
         } else {
             match (f64::try_from(self)?, f64::try_from(other)?) {
-                (_, b) if b == 0.0 => Err("Attempt to divide by zero"),
+                (_, 0.0) => Err("Attempt to divide by zero".to_owned()),
                 (a, b) => Ok(Self::Float(a / b)),
             }
         }
     }
 }
 
+/// Numbers are compared by value after widening both sides to `f64`, so
+/// `1 == 1.0` holds while `1 == 1.5` doesn't. An `Int` is never truncated to
+/// match a `Float`.
 impl PartialOrd for VariableValue {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        match (self, other) {
+            // Compared as integers so values past 2^53 keep their precision
+            (Self::Integer(a), Self::Integer(b)) => return a.partial_cmp(b),
+            // Mixed numbers are promoted to f64
+            #[allow(clippy::cast_precision_loss)]
+            (Self::Integer(a), Self::Float(b)) => return (*a as f64).partial_cmp(b),
+            #[allow(clippy::cast_precision_loss)]
+            (Self::Float(a), Self::Integer(b)) => return a.partial_cmp(&(*b as f64)),
+            (Self::Float(a), Self::Float(b)) => return a.partial_cmp(b),
+            // Strings are ordered lexicographically, by their bytes
+            (Self::String(a), Self::String(b)) => return a.partial_cmp(b),
+            _ => (),
+        }
         match (self.is_number(), other.is_number()) {
             (true, true) => match (f64::try_from(self), f64::try_from(other)) {
                 (Ok(a), Ok(b)) => a.partial_cmp(&b),
@@ -311,3 +383,6 @@ impl Not for VariableValue {
         Self::Bool(!bool::from(self))
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,140 @@
+use super::*;
+
+#[test]
+fn abs() {
+    assert_eq!(
+        VariableValue::Integer(-3).abs(),
+        Ok(VariableValue::Integer(3))
+    );
+    assert_eq!(
+        VariableValue::Integer(3).abs(),
+        Ok(VariableValue::Integer(3))
+    );
+    assert_eq!(
+        VariableValue::Float(-2.5).abs(),
+        Ok(VariableValue::Float(2.5))
+    );
+    assert!(VariableValue::Integer(i64::MIN).abs().is_err());
+}
+
+#[test]
+fn floor() {
+    assert_eq!(
+        VariableValue::Integer(-3).floor(),
+        Ok(VariableValue::Integer(-3))
+    );
+    assert_eq!(
+        VariableValue::Float(2.7).floor(),
+        Ok(VariableValue::Float(2.0))
+    );
+    assert_eq!(
+        VariableValue::Float(-2.2).floor(),
+        Ok(VariableValue::Float(-3.0))
+    );
+}
+
+#[test]
+fn ceil() {
+    assert_eq!(
+        VariableValue::Integer(4).ceil(),
+        Ok(VariableValue::Integer(4))
+    );
+    assert_eq!(
+        VariableValue::Float(2.2).ceil(),
+        Ok(VariableValue::Float(3.0))
+    );
+    assert_eq!(
+        VariableValue::Float(-2.7).ceil(),
+        Ok(VariableValue::Float(-2.0))
+    );
+}
+
+#[test]
+fn round() {
+    assert_eq!(
+        VariableValue::Integer(7).round(),
+        Ok(VariableValue::Integer(7))
+    );
+    assert_eq!(
+        VariableValue::Float(2.5).round(),
+        Ok(VariableValue::Float(3.0))
+    );
+    assert_eq!(
+        VariableValue::Float(2.4).round(),
+        Ok(VariableValue::Float(2.0))
+    );
+}
+
+#[test]
+fn bool_is_not_a_number() {
+    assert!(VariableValue::Bool(true).abs().is_err());
+    assert!(VariableValue::Bool(false).round().is_err());
+}
+
+#[test]
+fn int_and_float_compare_by_value() {
+    use std::cmp::Ordering;
+    let one = VariableValue::Integer(1);
+    assert_eq!(
+        one.partial_cmp(&VariableValue::Float(1.0)),
+        Some(Ordering::Equal)
+    );
+    assert_eq!(
+        one.partial_cmp(&VariableValue::Float(1.5)),
+        Some(Ordering::Less)
+    );
+    assert_eq!(
+        VariableValue::Float(1.5).partial_cmp(&one),
+        Some(Ordering::Greater)
+    );
+}
+
+#[test]
+fn mixed_numbers_are_promoted_to_float() {
+    use std::cmp::Ordering;
+    let two = VariableValue::Integer(2);
+    let three_and_half = VariableValue::Float(3.5);
+    assert_eq!(two.partial_cmp(&three_and_half), Some(Ordering::Less));
+    assert_eq!(three_and_half.partial_cmp(&two), Some(Ordering::Greater));
+    assert_eq!(
+        VariableValue::Float(-0.5).partial_cmp(&VariableValue::Integer(0)),
+        Some(Ordering::Less)
+    );
+    assert_eq!(two.partial_cmp(&VariableValue::Float(f64::NAN)), None);
+}
+
+#[test]
+fn ints_compare_without_losing_precision() {
+    use std::cmp::Ordering;
+    let big = 1 << 53;
+    assert_eq!(
+        VariableValue::Integer(big + 1).partial_cmp(&VariableValue::Integer(big)),
+        Some(Ordering::Greater)
+    );
+}
+
+#[test]
+fn strings_compare_lexicographically() {
+    use std::cmp::Ordering;
+    let string = |value: &str| VariableValue::String(value.to_owned());
+    assert_eq!(
+        string("ada").partial_cmp(&string("bob")),
+        Some(Ordering::Less)
+    );
+    assert_eq!(
+        string("bob").partial_cmp(&string("ada")),
+        Some(Ordering::Greater)
+    );
+    assert_eq!(
+        string("ada").partial_cmp(&string("ada")),
+        Some(Ordering::Equal)
+    );
+    assert_eq!(
+        string("ad").partial_cmp(&string("ada")),
+        Some(Ordering::Less)
+    );
+    assert_eq!(
+        string("Zoe").partial_cmp(&string("ada")),
+        Some(Ordering::Less)
+    );
+}
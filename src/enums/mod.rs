@@ -1,13 +1,15 @@
 use core::fmt;
 
+use serde::{Deserialize, Serialize};
+
 use crate::ast::ast_kind::AstNodeKind;
 use crate::ast::AstNode;
-use crate::dir_func::function::VariablesTable;
-use crate::dir_func::variable::Variable;
+use crate::dir_func::function::{GlobalScope, VariablesTable};
+use crate::dir_func::variable::{return_variable_name, undeclared_kind, Variable};
 use crate::error::error_kind::RaoulErrorKind;
 use crate::error::{RaoulError, Results};
 
-#[derive(Clone, Copy, PartialEq, Debug, Hash, Eq)]
+#[derive(Clone, Copy, PartialEq, Debug, Hash, Eq, Serialize, Deserialize)]
 pub enum Types {
     Int,
     Void,
@@ -51,8 +53,15 @@ impl Types {
         operator: Operator,
         rhs_type: Types,
     ) -> Result<Types, (Types, Types)> {
+        // A void function call has no value to operate with
+        if self == Types::Void {
+            return Err((self, rhs_type));
+        }
+        if rhs_type == Types::Void {
+            return Err((rhs_type, self));
+        }
         match operator {
-            Operator::Not | Operator::Or | Operator::And => {
+            Operator::Not | Operator::Or | Operator::And | Operator::Implies | Operator::Iff => {
                 let type_res = Types::Bool;
                 match (self.is_boolish(), rhs_type.is_boolish()) {
                     (true, true) => Ok(type_res),
@@ -62,6 +71,9 @@ impl Types {
             }
             Operator::Gte | Operator::Lte | Operator::Gt | Operator::Lt => {
                 let type_res = Types::Bool;
+                if self == Types::String && rhs_type == Types::String {
+                    return Ok(type_res);
+                }
                 match (self.is_number(), rhs_type.is_number()) {
                     (true, true) => Ok(type_res),
                     (true, false) => Err((rhs_type, type_res)),
@@ -74,6 +86,13 @@ impl Types {
                 }
                 Err((self, rhs_type))
             }
+            Operator::Contains | Operator::StartsWith | Operator::EndsWith => {
+                match (self, rhs_type) {
+                    (Types::String, Types::String) => Ok(Types::Bool),
+                    (Types::String, _) => Err((rhs_type, Types::String)),
+                    _ => Err((self, Types::String)),
+                }
+            }
             Operator::Sum | Operator::Minus | Operator::Times | Operator::Div => {
                 if self == rhs_type && self == Types::Int {
                     return Ok(Types::Int);
@@ -85,7 +104,7 @@ impl Types {
                     _ => Err((self, type_res)),
                 }
             }
-            _ => unreachable!("{:?}", operator),
+            _ => Err((self, rhs_type)),
         }
     }
 
@@ -116,42 +135,60 @@ impl Types {
     pub fn from_node<'a>(
         v: &AstNode<'a>,
         variables: &VariablesTable,
-        global: &VariablesTable,
+        global: &GlobalScope,
+        fail_fast: bool,
     ) -> Results<'a, Types> {
         match &v.kind {
-            AstNodeKind::Integer(_) | AstNodeKind::PureDataframeOp { .. } => Ok(Types::Int),
+            AstNodeKind::Integer(_)
+            | AstNodeKind::PureDataframeOp { .. }
+            | AstNodeKind::RandomInt { .. }
+            | AstNodeKind::UnaryDataframeOp {
+                operator: Operator::NullCount | Operator::ArgMin | Operator::ArgMax,
+                ..
+            } => Ok(Types::Int),
             AstNodeKind::Float(_)
+            | AstNodeKind::Random
             | AstNodeKind::UnaryDataframeOp { .. }
             | AstNodeKind::Correlation { .. } => Ok(Types::Float),
-            AstNodeKind::String(_) | AstNodeKind::Read => Ok(Types::String),
-            AstNodeKind::Bool(_) => Ok(Types::Bool),
+            AstNodeKind::String(_)
+            | AstNodeKind::Read(_)
+            | AstNodeKind::Argv(_)
+            | AstNodeKind::ColumnsOf { .. }
+            | AstNodeKind::Split { .. } => Ok(Types::String),
+            AstNodeKind::Bool(_) | AstNodeKind::ReadBool(_) => Ok(Types::Bool),
             AstNodeKind::Id(name) | AstNodeKind::ArrayVal { name, .. } => {
-                match Types::get_variable(name, variables, global) {
+                match Types::get_variable(name, variables, &global.variables) {
                     Some(variable) => Ok(variable.data_type),
-                    None => Err(RaoulError::new_vec(
-                        v,
-                        RaoulErrorKind::UndeclaredVar(name.to_string()),
-                    )),
+                    None => {
+                        let kind = undeclared_kind(name, [variables, &global.variables]);
+                        Err(RaoulError::new_vec(v, kind))
+                    }
                 }
             }
-            AstNodeKind::FuncCall { name, .. } => {
-                match Types::get_variable(name, variables, global) {
+            AstNodeKind::FuncCall { name, exprs } => {
+                let return_name = return_variable_name(name, exprs.len());
+                match global.variables.get(&return_name) {
                     Some(variable) => Ok(variable.data_type),
                     None => Err(RaoulError::new_vec(
                         v,
-                        RaoulErrorKind::UndeclaredFunction(name.to_string()),
+                        global.missing_return_kind(name, exprs.len(), variables),
                     )),
                 }
             }
             AstNodeKind::ArrayDeclaration { data_type, .. } => Ok(*data_type),
+            AstNodeKind::FilledArray { value, .. } => {
+                Types::from_node(value, variables, global, fail_fast)
+            }
             AstNodeKind::Array(exprs) => {
                 let types = RaoulError::create_partition(
+                    fail_fast,
                     exprs
                         .iter()
-                        .map(|node| Types::from_node(node, variables, global)),
+                        .map(|node| Types::from_node(node, variables, global, fail_fast)),
                 )?;
-                let first_type = *(types.get(0).unwrap());
+                let first_type = *(types.first().unwrap());
                 RaoulError::create_results(
+                    fail_fast,
                     types
                         .into_iter()
                         .zip(exprs)
@@ -160,17 +197,21 @@ impl Types {
                 Ok(first_type)
             }
             AstNodeKind::BinaryOperation { operator, lhs, rhs } => {
-                let lhs_type = Types::from_node(&*lhs, variables, global)?;
-                let rhs_type = Types::from_node(&*rhs, variables, global)?;
+                let lhs_type = Types::from_node(lhs, variables, global, fail_fast)?;
+                let rhs_type = Types::from_node(rhs, variables, global, fail_fast)?;
                 lhs_type.assert_bin_op(*operator, rhs_type, v)
             }
             AstNodeKind::UnaryOperation { operator, operand } => match operator {
                 Operator::Not => {
-                    let operand_type = Types::from_node(&*operand, variables, global)?;
+                    let operand_type = Types::from_node(operand, variables, global, fail_fast)?;
                     let res_type = Types::Bool;
                     operand_type.assert_cast(res_type, v)?;
                     Ok(res_type)
                 }
+                Operator::ToString => {
+                    Types::from_node(operand, variables, global, fail_fast)?;
+                    Ok(Types::String)
+                }
                 _ => unreachable!("{:?}", operator),
             },
             AstNodeKind::ReadCSV(_) => Ok(Self::Dataframe),
@@ -179,12 +220,29 @@ impl Types {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Debug, Hash, Eq)]
+/// The type as it's written in the source
+impl fmt::Display for Types {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Types::Int => "int",
+            Types::Void => "void",
+            Types::Float => "float",
+            Types::String => "string",
+            Types::Bool => "bool",
+            Types::Dataframe => "dataframe",
+        };
+        f.pad(name)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Hash, Eq, Serialize, Deserialize)]
 pub enum Operator {
     // Boolean
     Not,
     Or,
     And,
+    Implies,
+    Iff,
     // Relational
     Gte,
     Lte,
@@ -199,14 +257,29 @@ pub enum Operator {
     Times,
     Div,
     Inc,
+    Dec,
+    // Strings
+    Contains,
+    StartsWith,
+    EndsWith,
+    ToString,
     // ByteCode
     Assignment,
     Print,
     PrintNl,
     Read,
+    ReadBool,
+    Argv,
+    Random,
+    RandomInt,
     Goto,
     GotoF,
     End,
+    Assert,
+    Abort,
+    Append,
+    Try,
+    EndTry,
     // Functions
     Return,
     EndProc,
@@ -215,6 +288,9 @@ pub enum Operator {
     Param,
     // Arrays
     Ver,
+    VerCount,
+    Split,
+    SplitParts,
     // Dataframe
     Rows,
     Columns,
@@ -225,20 +301,109 @@ pub enum Operator {
     Min,
     Max,
     Range,
+    NullCount,
+    First,
+    Last,
+    ArgMin,
+    ArgMax,
     Corr,
     ReadCSV,
+    ColumnsOf,
     Plot,
     Histogram,
+    Density,
+    CastColumn,
+    Describe,
+    Show,
 }
 
 impl Operator {
     pub fn is_goto(self) -> bool {
-        matches!(self, Operator::Goto | Operator::GotoF)
+        matches!(self, Operator::Goto | Operator::GotoF | Operator::Try)
+    }
+
+    /// Token of the operator in the source code. Operators that only exist
+    /// in the bytecode use their name instead
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Operator::Not => "NOT",
+            Operator::Or => "OR",
+            Operator::And => "AND",
+            Operator::Implies => "IMPLIES",
+            Operator::Iff => "IFF",
+            Operator::Gte => ">=",
+            Operator::Lte => "<=",
+            Operator::Gt => ">",
+            Operator::Lt => "<",
+            Operator::Eq => "==",
+            Operator::Ne => "!=",
+            Operator::Sum => "+",
+            Operator::Minus => "-",
+            Operator::Times => "*",
+            Operator::Div => "/",
+            Operator::Contains => "contains",
+            Operator::StartsWith => "startswith",
+            Operator::EndsWith => "endswith",
+            Operator::ToString => "to_string",
+            Operator::Assignment => "=",
+            Operator::Print => "print",
+            Operator::Read => "input",
+            Operator::ReadBool => "read_bool",
+            Operator::Argv => "argv",
+            Operator::Random => "random",
+            Operator::RandomInt => "random_int",
+            Operator::Assert => "assert",
+            Operator::Abort => "error",
+            Operator::Append => "append",
+            Operator::Return => "return",
+            Operator::Split => "split",
+            Operator::Rows => "get_rows",
+            Operator::Columns => "get_columns",
+            Operator::Average => "average",
+            Operator::Std => "std",
+            Operator::Median => "median",
+            Operator::Variance => "variance",
+            Operator::Min => "min",
+            Operator::Max => "max",
+            Operator::Range => "range",
+            Operator::NullCount => "null_count",
+            Operator::First => "first",
+            Operator::Last => "last",
+            Operator::ArgMin => "argmin",
+            Operator::ArgMax => "argmax",
+            Operator::Corr => "correlation",
+            Operator::ReadCSV => "read_csv",
+            Operator::ColumnsOf => "columns_of",
+            Operator::Plot => "plot",
+            Operator::Histogram => "histogram",
+            Operator::CastColumn => "cast_column",
+            Operator::Describe => "describe",
+            Operator::Show => "show",
+            Operator::Try => "try",
+            Operator::Inc => "Inc",
+            Operator::Dec => "Dec",
+            Operator::PrintNl => "PrintNl",
+            Operator::Goto => "Goto",
+            Operator::GotoF => "GotoF",
+            Operator::EndTry => "EndTry",
+            Operator::End => "End",
+            Operator::EndProc => "EndProc",
+            Operator::Era => "Era",
+            Operator::GoSub => "GoSub",
+            Operator::Param => "Param",
+            Operator::Ver => "Ver",
+            Operator::VerCount => "VerCount",
+            Operator::SplitParts => "SplitParts",
+            Operator::Density => "Density",
+        }
     }
 }
 
 impl fmt::Display for Operator {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{:10}", format!("{:?}", self))
+        write!(f, "{:11}", self.symbol())
     }
 }
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,46 @@
+use super::*;
+
+const BINARY_OPERATORS: [Operator; 17] = [
+    Operator::Or,
+    Operator::And,
+    Operator::Implies,
+    Operator::Iff,
+    Operator::Gte,
+    Operator::Lte,
+    Operator::Gt,
+    Operator::Lt,
+    Operator::Eq,
+    Operator::Ne,
+    Operator::Sum,
+    Operator::Minus,
+    Operator::Times,
+    Operator::Div,
+    Operator::Contains,
+    Operator::StartsWith,
+    Operator::EndsWith,
+];
+
+#[test]
+fn void_operands_are_rejected() {
+    let types = [
+        Types::Int,
+        Types::Float,
+        Types::String,
+        Types::Bool,
+        Types::Void,
+    ];
+    for operator in BINARY_OPERATORS {
+        for data_type in types {
+            let res = Types::Void.binary_operator_type(operator, data_type);
+            assert_eq!(res, Err((Types::Void, data_type)), "{operator:?}");
+            let res = data_type.binary_operator_type(operator, Types::Void);
+            assert_eq!(res, Err((Types::Void, data_type)), "{operator:?}");
+        }
+    }
+}
+
+#[test]
+fn unexpected_operators_are_rejected() {
+    let res = Types::Int.binary_operator_type(Operator::Goto, Types::Int);
+    assert!(res.is_err());
+}
@@ -1,14 +1,18 @@
 use core::fmt;
 
-use crate::enums::Types;
+use crate::{address::THRESHOLD, dir_func::variable::Dimensions, enums::Types};
 
-#[derive(PartialEq, Eq, Clone)]
+#[derive(PartialEq, Eq, Clone, Debug)]
 #[allow(clippy::module_name_repetitions)]
 pub enum RaoulErrorKind {
-    MemoryExceded,
+    MemoryExceded {
+        data_type: Types,
+        requested: usize,
+    },
     UndeclaredVar(String),
     UndeclaredFunction(String),
-    UndeclaredFunction2(String),
+    VoidFunction(String),
+    NotCallable(String),
     RedeclaredFunction(String),
     RedefinedType {
         name: String,
@@ -20,56 +24,137 @@ pub enum RaoulErrorKind {
         to: Types,
     },
     UnmatchArgsAmount {
+        name: String,
+        expected: usize,
+        given: usize,
+    },
+    UnmatchReturnsAmount {
+        name: String,
         expected: usize,
         given: usize,
     },
-    MissingReturn(String),
+    MissingReturn {
+        name: String,
+        return_type: Types,
+    },
     NotList(String),
     NotMatrix(String),
+    MatrixNotList {
+        name: String,
+        rows: usize,
+        columns: usize,
+    },
     UsePrimitive,
     InconsistentSize {
         expected: Option<usize>,
         given: Option<usize>,
     },
+    MismatchedShape {
+        name: String,
+        expected: Dimensions,
+        given: Dimensions,
+    },
     OnlyOneDataframe,
+    MissingMain,
+    DegenerateLoop {
+        start: i64,
+        stop: i64,
+    },
+    NegativeRepeat(i64),
+    RedeclaredRecord(String),
+    UndeclaredRecord(String),
+    UnknownField {
+        record: String,
+        field: String,
+    },
+    MissingField {
+        record: String,
+        field: String,
+    },
+    MismatchedRecord {
+        name: String,
+        expected: String,
+        given: String,
+    },
+    WholeRecord {
+        name: String,
+        record: String,
+    },
+}
+
+/// Shape of an array, such as "a list of 3" or "a 2x3 matrix"
+fn shape(dimensions: Dimensions) -> String {
+    match dimensions {
+        (Some(rows), Some(columns)) => format!("a {rows}x{columns} matrix"),
+        (Some(size), None) => format!("a list of {size}"),
+        _ => "a single value".to_owned(),
+    }
 }
 
-impl fmt::Debug for RaoulErrorKind {
+impl fmt::Display for RaoulErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::UsePrimitive => write!(f, "We can't handle using the complete array"),
             Self::UndeclaredVar(name) => write!(f, "Variable \"{name}\" was not declared"),
             Self::UndeclaredFunction(name) => {
-                write!(
-                    f,
-                    "Function \"{name}\" was not declared or does not return a non-void value",
-                )
-            }
-            Self::UndeclaredFunction2(name) => {
                 write!(f, "Function \"{name}\" was not declared")
             }
+            Self::NotCallable(name) => {
+                write!(f, "`{name}` is a variable, so it can't be called")
+            }
+            Self::VoidFunction(name) => {
+                write!(f, "Function \"{name}\" does not return a value to be used")
+            }
             Self::RedeclaredFunction(name) => {
                 write!(f, "Function \"{name}\" was already declared before")
             }
             Self::RedefinedType { name, from, to } => {
                 write!(
                     f,
-                    "\"{name}\" was originally defined as {from:?} and you're attempting to redefined it as a {to:?}",
+                    "\"{name}\" was originally defined as {from} and you're attempting to redefined it as a {to}",
                 )
             }
-            Self::InvalidCast { from, to } => write!(f, "Cannot cast from {from:?} to {to:?}"),
-            Self::MemoryExceded => write!(f, "Memory was exceded"),
-            Self::UnmatchArgsAmount { expected, given } => {
+            Self::InvalidCast { from, to } => write!(f, "Cannot cast from {from} to {to}"),
+            Self::MemoryExceded {
+                data_type,
+                requested,
+            } => write!(
+                f,
+                "Memory was exceded: there's no room for {requested} more {data_type} value(s), each scope holds up to {THRESHOLD} of each type"
+            ),
+            Self::UnmatchArgsAmount {
+                name,
+                expected,
+                given,
+            } => {
                 write!(
                     f,
-                    "Wrong args amount: Expected {expected}, but were given {given}"
+                    "Wrong args amount: Function \"{name}\" takes {expected} argument(s), but {given} were given"
                 )
             }
-            Self::MissingReturn(name) => {
-                write!(f, "In function {name} not all branches return a value")
+            Self::UnmatchReturnsAmount {
+                name,
+                expected,
+                given,
+            } => {
+                write!(
+                    f,
+                    "Wrong returns amount: Function \"{name}\" returns {expected} value(s), but {given} were used"
+                )
+            }
+            Self::MissingReturn { name, return_type } => {
+                write!(
+                    f,
+                    "In function {name} not all branches return a value of type {return_type}"
+                )
             }
             Self::NotList(name) => write!(f, "`{name}` is not a list"),
             Self::NotMatrix(name) => write!(f, "`{name}` is not a matrix"),
+            Self::MatrixNotList {
+                name,
+                rows,
+                columns,
+            } => write!(f, "`{name}` is a {rows}x{columns} matrix, not a list"),
             Self::InconsistentSize { expected, given } => {
                 write!(
                     f,
@@ -78,7 +163,49 @@ impl fmt::Debug for RaoulErrorKind {
                     given.unwrap_or(0)
                 )
             }
+            Self::MismatchedShape {
+                name,
+                expected,
+                given,
+            } => write!(
+                f,
+                "`{name}` is {} and can't be assigned {}",
+                shape(*expected),
+                shape(*given)
+            ),
             Self::OnlyOneDataframe => write!(f, "Only one dataframe is allowed per program"),
+            Self::MissingMain => write!(f, "The program is missing a `main` function"),
+            Self::DegenerateLoop { start, stop } => {
+                write!(
+                    f,
+                    "The loop goes from {start} to {stop}, so its body would never be executed"
+                )
+            }
+            Self::NegativeRepeat(count) => {
+                write!(f, "Can't repeat a loop a negative amount of times ({count})")
+            }
+            Self::RedeclaredRecord(name) => {
+                write!(f, "Struct \"{name}\" was already declared before")
+            }
+            Self::UndeclaredRecord(name) => write!(f, "Struct \"{name}\" was not declared"),
+            Self::UnknownField { record, field } => {
+                write!(f, "Struct \"{record}\" has no field \"{field}\"")
+            }
+            Self::MissingField { record, field } => {
+                write!(f, "Field \"{field}\" of struct \"{record}\" was not given a value")
+            }
+            Self::MismatchedRecord {
+                name,
+                expected,
+                given,
+            } => write!(
+                f,
+                "\"{name}\" was originally defined as {expected} and can't be redefined as {given}"
+            ),
+            Self::WholeRecord { name, record } => write!(
+                f,
+                "\"{name}\" is a {record} struct, which can't be assigned or used as a whole, only its fields"
+            ),
         }
     }
 }
@@ -12,22 +12,24 @@ use crate::parser::Rule;
 
 use self::error_kind::RaoulErrorKind;
 
-#[derive(PartialEq, Eq, Clone)]
+#[derive(PartialEq, Eq, Clone, Debug)]
 #[allow(clippy::module_name_repetitions)]
 pub struct RaoulError<'a> {
     kind: RaoulErrorKind,
     span: Span<'a>,
 }
 
-impl fmt::Debug for RaoulError<'_> {
+impl fmt::Display for RaoulError<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let message = format!("{:?}", self.kind);
+        let message = self.kind.to_string();
         let error: Error<Rule> =
             Error::new_from_span(ErrorVariant::CustomError { message }, self.span.clone());
         write!(f, "{}", error)
     }
 }
 
+impl std::error::Error for RaoulError<'_> {}
+
 impl RaoulError<'_> {
     pub fn new<'a>(node: &AstNode<'a>, kind: RaoulErrorKind) -> RaoulError<'a> {
         RaoulError {
@@ -41,18 +43,23 @@ impl RaoulError<'_> {
     }
 
     fn from_results_iter<'a, T, I: IntoIterator<Item = Results<'a, T>>>(
+        fail_fast: bool,
         iter: I,
     ) -> Vec<RaoulError<'a>> {
-        iter.into_iter()
-            .filter_map(Results::err)
-            .flatten()
-            .collect()
+        let mut errors = iter.into_iter().filter_map(Results::err);
+        if fail_fast {
+            return errors.next().unwrap_or_default();
+        }
+        errors.flatten().collect()
     }
 
+    /// Runs every result, stopping at the first error when `fail_fast` is
+    /// set, and collects their errors
     pub fn create_results<'a, T, I: IntoIterator<Item = Results<'a, T>>>(
+        fail_fast: bool,
         iter: I,
     ) -> Results<'a, ()> {
-        let errors = RaoulError::from_results_iter(iter);
+        let errors = RaoulError::from_results_iter(fail_fast, iter);
         if errors.is_empty() {
             Ok(())
         } else {
@@ -61,8 +68,12 @@ impl RaoulError<'_> {
     }
 
     pub fn create_partition<'a, T: Debug, I: IntoIterator<Item = Results<'a, T>>>(
+        fail_fast: bool,
         iter: I,
     ) -> Results<'a, Vec<T>> {
+        if fail_fast {
+            return iter.into_iter().collect();
+        }
         let (oks, errors): (Vec<_>, Vec<_>) = iter.into_iter().partition(Results::is_ok);
         if errors.is_empty() {
             Ok(oks.into_iter().map(Results::unwrap).collect())
@@ -0,0 +1,112 @@
+use crate::enums::Types;
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Tokens of the operators that can be used in expressions, checked against
+/// the grammar by the tests
+const OPERATORS: [&str; 15] = [
+    "NOT", "AND", "OR", "IMPLIES", "IFF", "==", "!=", ">=", "<=", ">", "<", "-", "+", "/", "*",
+];
+
+/// Words reserved by the `KEYWORD_TYPE` rule of the grammar, checked against it
+/// by the tests
+const KEYWORDS: [&str; 67] = [
+    "AND",
+    "OR",
+    "NOT",
+    "IMPLIES",
+    "IFF",
+    "bool",
+    "float",
+    "int",
+    "string",
+    "void",
+    "func",
+    "main",
+    "if",
+    "else",
+    "printjoin",
+    "print",
+    "while",
+    "repeat",
+    "try",
+    "catch",
+    "for",
+    "match",
+    "global",
+    "input",
+    "read_bool",
+    "argv",
+    "random_int",
+    "random",
+    "struct",
+    "true",
+    "True",
+    "false",
+    "False",
+    "read_csv",
+    "columns_of",
+    "split",
+    "to_string",
+    "get_rows",
+    "get_columns",
+    "average",
+    "std",
+    "median",
+    "variance",
+    "min",
+    "max",
+    "range",
+    "null_count",
+    "first",
+    "last",
+    "argmin",
+    "argmax",
+    "contains",
+    "startswith",
+    "endswith",
+    "correlation",
+    "plot",
+    "histogram",
+    "describe",
+    "show",
+    "cast_column",
+    "return",
+    "assert",
+    "error",
+    "declare_arr",
+    "fill",
+    "in",
+    "to",
+];
+
+/// Capabilities supported by this version of the compiler, meant for tooling
+/// such as editor extensions.
+#[derive(Clone, PartialEq, Debug)]
+pub struct LanguageInfo {
+    pub version: &'static str,
+    pub types: Vec<Types>,
+    /// Tokens of the operators that can be used in expressions
+    pub operators: Vec<String>,
+    /// Words that can't be used as identifiers
+    pub keywords: Vec<String>,
+}
+
+pub fn language_info() -> LanguageInfo {
+    LanguageInfo {
+        version: VERSION,
+        types: vec![
+            Types::Int,
+            Types::Float,
+            Types::String,
+            Types::Bool,
+            Types::Void,
+            Types::Dataframe,
+        ],
+        operators: OPERATORS.map(str::to_owned).to_vec(),
+        keywords: KEYWORDS.map(str::to_owned).to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,91 @@
+use super::*;
+use crate::{eval, parse};
+
+const GRAMMAR: &str = include_str!("../parser/grammar.pest");
+
+/// Rules of the grammar matching the operators used inside expressions
+const OPERATOR_RULES: [&str; 9] = [
+    "not", "AND", "OR", "IMPLIES", "IFF", "comp_op", "rel_op", "art_op", "fact_op",
+];
+
+/// Body of a rule of the grammar, without its comments
+fn rule_body(name: &str) -> String {
+    let definition = GRAMMAR
+        .lines()
+        .position(|line| {
+            let mut words = line.split_whitespace();
+            words.next() == Some(name) && words.next() == Some("=")
+        })
+        .unwrap_or_else(|| panic!("The grammar has no {name} rule"));
+    let mut body = String::new();
+    let mut depth = 0;
+    for line in GRAMMAR.lines().skip(definition) {
+        let line = line.split("//").next().unwrap();
+        for c in line.chars() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                c if depth > 0 => body.push(c),
+                _ => (),
+            }
+        }
+        body.push(' ');
+        if depth == 0 && !body.trim().is_empty() {
+            return body;
+        }
+    }
+    panic!("The {name} rule is never closed")
+}
+
+/// The literals a rule matches, following the rules it references
+fn literals(name: &str) -> Vec<String> {
+    let body = rule_body(name);
+    let mut literals = Vec::new();
+    for (i, part) in body.split('"').enumerate() {
+        if i % 2 == 1 {
+            literals.push(part.to_owned());
+            continue;
+        }
+        part.split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .filter(|rule| !rule.is_empty())
+            .for_each(|rule| literals.extend(self::literals(rule)));
+    }
+    literals
+}
+
+#[test]
+fn operators_match_the_grammar() {
+    let grammar: Vec<_> = OPERATOR_RULES.into_iter().flat_map(literals).collect();
+    assert_eq!(language_info().operators, grammar);
+}
+
+#[test]
+fn keywords_match_the_grammar() {
+    assert_eq!(language_info().keywords, literals("KEYWORD_TYPE"));
+}
+
+#[test]
+fn operators_can_be_used_in_expressions() {
+    let operators = language_info().operators;
+    assert!(operators.iter().any(|operator| operator == "IMPLIES"));
+    operators.into_iter().for_each(|operator| {
+        let expr = match operator.as_str() {
+            "NOT" => "NOT 1".to_owned(),
+            _ => format!("1 {operator} 1"),
+        };
+        let program = format!("func main(): void {{ print({expr}); }}");
+        assert!(eval(&program).is_ok(), "{operator}");
+    });
+}
+
+#[test]
+fn keywords_cant_be_identifiers() {
+    let keywords = language_info().keywords;
+    for keyword in ["split", "first", "argmax", "repeat", "try", "catch", "True"] {
+        assert!(keywords.iter().any(|word| word == keyword), "{keyword}");
+    }
+    keywords.into_iter().for_each(|keyword| {
+        let program = format!("func main(): void {{ {keyword} = 1; }}");
+        assert!(parse(&program, false).is_err(), "{keyword}");
+    });
+}
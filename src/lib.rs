@@ -0,0 +1,71 @@
+// ANCHOR: Actual parser
+pub mod address;
+pub mod ast;
+pub mod dir_func;
+pub mod enums;
+pub mod error;
+pub mod info;
+pub mod parser;
+pub mod quadruple;
+pub mod vm;
+
+use ast::AstNode;
+use dir_func::DirFunc;
+use error::Results;
+pub use info::{language_info, LanguageInfo, VERSION};
+pub use parser::parse;
+use quadruple::quadruple_manager::QuadrupleManager;
+
+// ANCHOR: Testing the examples
+mod test_parser;
+#[macro_use]
+extern crate pest_derive;
+
+/// How `parse_ast` compiles a program
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct CompileOptions {
+    /// Prints the function directory and the quads
+    pub debug: bool,
+    /// Prints the quads
+    pub quads: bool,
+    /// Stops at the first error instead of reporting all of them
+    pub fail_fast: bool,
+    /// Runs the optimizer over the quads
+    pub optimize: bool,
+}
+
+pub fn parse_ast<'a>(ast: &'a AstNode, options: CompileOptions) -> Results<'a, QuadrupleManager> {
+    let mut dir_func = DirFunc::new();
+    dir_func.build_dir_func(ast, options.fail_fast)?;
+    if options.debug {
+        println!("Dir func created sucessfully");
+        println!("{:#?}", dir_func);
+    }
+    let mut quad_manager = QuadrupleManager::new(dir_func, options.fail_fast);
+    quad_manager.parse(ast)?;
+    if options.optimize {
+        quad_manager.optimize();
+    }
+    if options.debug || options.quads {
+        println!("Quads created sucessfully");
+        println!("{}", quad_manager.annotated());
+    }
+    quad_manager.clear_variables();
+    Ok(quad_manager)
+}
+
+/// Compiles and runs the program in `source` without printing or showing its
+/// charts, returning the messages it printed or the errors that stopped it
+pub fn eval(source: &str) -> Result<Vec<String>, String> {
+    let ast = parse(source, false).map_err(|error| format!("Parsing error {error}"))?;
+    let quad_manager = parse_ast(&ast, CompileOptions::default()).map_err(|errors| {
+        let messages: Vec<_> = errors.iter().map(ToString::to_string).collect();
+        messages.join("\n")
+    })?;
+    let mut vm = vm::VM::new(&quad_manager, false, false);
+    vm.run_collect()?;
+    Ok(vm.messages)
+}
+
+#[cfg(test)]
+mod tests;
@@ -1,57 +1,127 @@
 mod args;
 
-// ANCHOR: Actual parser
-mod address;
-mod ast;
-mod dir_func;
-mod enums;
-mod error;
-mod parser;
-mod quadruple;
-mod vm;
+use std::{io::Read, process::exit};
 
-use ast::AstNode;
-use dir_func::DirFunc;
-use error::Results;
-use parser::parse;
-use quadruple::quadruple_manager::QuadrupleManager;
-use vm::VM;
+use args::{is_debug, parse_arguments};
+use clap::ArgMatches;
+use raoul::{
+    ast::printer::format_program, dir_func::DirFunc, parse, parse_ast,
+    quadruple::bytecode::Bytecode, vm::VM, CompileOptions,
+};
 
-// ANCHOR: Testing the examples
-mod test_parser;
-#[macro_use]
-extern crate pest_derive;
+/// Line separating the programs given to `--batch`
+const BATCH_SEPARATOR: &str = "---";
 
-use std::process::exit;
+fn format_file(filename: &str) {
+    let source = std::fs::read_to_string(filename).expect(filename);
+    match parse(&source, false) {
+        Ok(ast) => print!("{}", format_program(&ast)),
+        Err(error) => {
+            println!("Parsing error {}", error);
+            exit(1);
+        }
+    }
+}
 
-use args::parse_arguments;
+/// Applies the flags shared by every way of running a program
+fn configure_vm(vm: &mut VM, matches: &ArgMatches) {
+    if let Some(args) = matches.values_of("args") {
+        vm.set_args(args.map(String::from).collect());
+    }
+    if let Some(seed) = matches.value_of("seed") {
+        vm.set_seed(seed.parse().expect("validated by clap"));
+    }
+    if let Some(stack_cap) = matches.value_of("stack-cap") {
+        vm.set_stack_cap(stack_cap.parse().expect("validated by clap"));
+    }
+    if let Some(max_steps) = matches.value_of("max-steps") {
+        vm.set_max_steps(max_steps.parse().expect("validated by clap"));
+    }
+}
 
-fn parse_ast<'a>(ast: &'a AstNode, debug: bool, quads: bool) -> Results<'a, QuadrupleManager> {
-    let mut dir_func = DirFunc::new();
-    dir_func.build_dir_func(ast)?;
-    if debug {
-        println!("Dir func created sucessfully");
-        println!("{:#?}", dir_func);
+/// Compiles and runs one program of a batch, returning what it printed and
+/// the errors that stopped it
+fn run_batch_program(source: &str, matches: &ArgMatches) -> (String, Vec<String>) {
+    let ast = match parse(source, false) {
+        Ok(ast) => ast,
+        Err(error) => return (String::new(), vec![format!("Parsing error {}", error)]),
+    };
+    let options = CompileOptions {
+        fail_fast: matches.is_present("fail-fast"),
+        optimize: matches.is_present("optimize"),
+        ..CompileOptions::default()
+    };
+    let quad_manager = match parse_ast(&ast, options) {
+        Ok(quad_manager) => quad_manager,
+        Err(errors) => {
+            return (
+                String::new(),
+                errors.iter().map(ToString::to_string).collect(),
+            )
+        }
+    };
+    let mut vm = VM::new(&quad_manager, false, matches.is_present("bool-as-int"));
+    // Stdin holds the programs, so there is nothing left to read
+    vm.set_input(Vec::new());
+    configure_vm(&mut vm, matches);
+    let (output, res) = vm.run_capture();
+    let errors = res.err().map(|error| format!("[Error]: {error}"));
+    (output, errors.into_iter().collect())
+}
+
+/// Runs each program read from stdin on its own, so an error in one of them
+/// doesn't stop the rest, and prints a `[program N] ok|error` header followed
+/// by its output and errors
+fn run_batch(matches: &ArgMatches) {
+    let mut input = String::new();
+    std::io::stdin()
+        .read_to_string(&mut input)
+        .expect("stdin must be readable");
+    let mut programs = vec![String::new()];
+    for line in input.lines() {
+        match line.trim() == BATCH_SEPARATOR {
+            true => programs.push(String::new()),
+            false => {
+                let program = programs.last_mut().expect("starts with one program");
+                program.push_str(line);
+                program.push('\n');
+            }
+        }
     }
-    let mut quad_manager = QuadrupleManager::new(dir_func);
-    quad_manager.parse(ast)?;
-    if debug || quads {
-        println!("Quads created sucessfully");
-        println!("{}", quad_manager);
+    let programs = programs.iter().filter(|program| !program.trim().is_empty());
+    for (index, program) in programs.enumerate() {
+        let (output, errors) = run_batch_program(program, matches);
+        let status = if errors.is_empty() { "ok" } else { "error" };
+        println!("[program {}] {status}", index + 1);
+        print!("{output}");
+        if !output.is_empty() && !output.ends_with('\n') {
+            println!();
+        }
+        for error in errors {
+            println!("{error}");
+        }
     }
-    quad_manager.clear_variables();
-    Ok(quad_manager)
 }
 
-fn main() {
-    let matches = parse_arguments();
-    let filename = matches.value_of("file").expect("required");
-    let debug = matches.is_present("debug");
-    let quads = matches.is_present("quads");
+/// Compiles the file or source given, returning `None` when the flags ask to
+/// stop before running it
+fn compile(matches: &ArgMatches, debug: bool) -> Option<Bytecode> {
+    let options = CompileOptions {
+        debug,
+        quads: matches.is_present("quads"),
+        fail_fast: matches.is_present("fail-fast"),
+        optimize: matches.is_present("optimize"),
+    };
     if debug {
         println!("Starting parsing");
     }
-    let file = std::fs::read_to_string(filename).expect(filename);
+    let file = match matches.value_of("eval") {
+        Some(source) => source.to_owned(),
+        None => {
+            let filename = matches.value_of("file").expect("required");
+            std::fs::read_to_string(filename).expect(filename)
+        }
+    };
     let parsing_response = parse(&file, debug);
     if let Err(error) = parsing_response {
         println!("Parsing error {}", error);
@@ -62,20 +132,72 @@ fn main() {
         println!("Parsing ended sucessfully");
         println!("AST:\n{:?}", ast);
     }
-    let res = parse_ast(&ast, debug, quads);
+    if matches.is_present("ast-only") {
+        println!("{:?}", ast);
+        return None;
+    }
+    if matches.is_present("dump-symbols") {
+        let mut dir_func = DirFunc::new();
+        if dir_func.build_dir_func(&ast, options.fail_fast).is_ok() {
+            println!("{}", dir_func.symbols());
+        }
+    }
+    let res = parse_ast(&ast, options);
     if let Err(errors) = res {
         for error in errors {
-            println!("{:?}", error);
+            println!("{error}");
         }
         exit(1);
     }
     let quad_manager = res.unwrap();
-    let mut vm = VM::new(&quad_manager, debug);
+    if let Some(path) = matches.value_of("emit-dot") {
+        std::fs::write(path, quad_manager.to_dot()).expect(path);
+    }
+    let bytecode = Bytecode::from(&quad_manager);
+    if let Some(path) = matches.value_of("emit-bytecode") {
+        std::fs::write(path, bytecode.to_json()).expect(path);
+        return None;
+    }
+    Some(bytecode)
+}
+
+fn load_bytecode(path: &str) -> Bytecode {
+    let json = std::fs::read_to_string(path).expect(path);
+    match Bytecode::from_json(&json) {
+        Ok(bytecode) => bytecode,
+        Err(error) => {
+            println!("{error}");
+            exit(1);
+        }
+    }
+}
+
+fn main() {
+    let matches = parse_arguments();
+    if let Some(matches) = matches.subcommand_matches("fmt") {
+        format_file(matches.value_of("file").expect("required"));
+        return;
+    }
+    if matches.is_present("batch") {
+        run_batch(&matches);
+        return;
+    }
+    let debug = is_debug(&matches);
+    let bytecode = match matches.value_of("run-bytecode") {
+        Some(path) => load_bytecode(path),
+        None => match compile(&matches, debug) {
+            Some(bytecode) => bytecode,
+            None => return,
+        },
+    };
+    let bool_as_int = matches.is_present("bool-as-int");
+    let mut vm = VM::from_bytecode(bytecode, debug, bool_as_int);
+    configure_vm(&mut vm, &matches);
+    vm.set_flush(matches.is_present("flush"));
+    vm.set_crlf(matches.value_of("line-endings") == Some("crlf"));
+    vm.set_bom(matches.is_present("bom"));
     if let Err(error) = vm.run() {
         println!("[Error]: {error}");
         exit(1);
     }
 }
-
-#[cfg(test)]
-mod tests;
@@ -1,3 +1,6 @@
+#![allow(clippy::result_large_err)]
+use std::collections::HashMap;
+
 use pest_consume::match_nodes;
 use pest_consume::Parser;
 
@@ -9,10 +12,18 @@ use crate::enums::{Operator, Types};
 #[grammar = "parser/grammar.pest"] // relative to src
 struct LanguageParser;
 
+use pest::error::ErrorVariant;
 use pest_consume::Error;
 type Result<T> = std::result::Result<T, Error<Rule>>;
 type Node<'i> = pest_consume::Node<'i, Rule, bool>;
 
+/// Size of the array created by `split` when none is given
+pub const SPLIT_PARTS: usize = 16;
+/// Size of the array created by `columns_of` when none is given
+pub const COLUMNS_OF_SIZE: usize = 16;
+/// Most values a function can return
+const MAX_RETURNS: usize = 4;
+
 // This is the other half of the parser, using pest_consume.
 #[pest_consume::parser]
 impl LanguageParser {
@@ -46,6 +57,10 @@ impl LanguageParser {
         Ok(Types::Bool)
     }
 
+    fn dataframe(input: Node) -> Result<Types> {
+        Ok(Types::Dataframe)
+    }
+
     fn atomic_types(input: Node) -> Result<Types> {
         Ok(match_nodes!(input.into_children();
             [int(value)] => value,
@@ -62,6 +77,13 @@ impl LanguageParser {
         ))
     }
 
+    fn arg_types(input: Node) -> Result<Types> {
+        Ok(match_nodes!(input.into_children();
+            [atomic_types(value)] => value,
+            [dataframe(value)] => value,
+        ))
+    }
+
     // Operations
     fn not(input: Node) -> Result<Operator> {
         Ok(Operator::Not)
@@ -115,6 +137,21 @@ impl LanguageParser {
         Ok(Operator::Minus)
     }
 
+    fn inc_op(input: Node) -> Result<Operator> {
+        Ok(Operator::Inc)
+    }
+
+    fn dec_op(input: Node) -> Result<Operator> {
+        Ok(Operator::Dec)
+    }
+
+    fn step_op(input: Node) -> Result<Operator> {
+        Ok(match_nodes!(input.into_children();
+            [inc_op(value)] => value,
+            [dec_op(value)] => value,
+        ))
+    }
+
     fn art_op(input: Node) -> Result<Operator> {
         Ok(match_nodes!(input.into_children();
             [sum(value)] => value,
@@ -139,11 +176,8 @@ impl LanguageParser {
 
     // Values
     fn int_cte(input: Node) -> Result<AstNode> {
-        let value = input
-            .as_str()
-            .parse::<i64>()
-            .map_err(|e| input.error(e))
-            .unwrap();
+        let digits = input.as_str().replace('_', "");
+        let value = digits.parse::<i64>().map_err(|e| input.error(e))?;
         let kind = AstNodeKind::Integer(value);
         Ok(AstNode {
             kind,
@@ -152,30 +186,33 @@ impl LanguageParser {
     }
 
     fn float_cte(input: Node) -> Result<AstNode> {
-        let value = input
-            .as_str()
-            .parse::<f64>()
-            .map_err(|e| input.error(e))
-            .unwrap();
+        let digits = input.as_str().replace('_', "");
+        let value = digits.parse::<f64>().map_err(|e| input.error(e))?;
         Ok(AstNode {
             kind: AstNodeKind::Float(value),
             span: input.as_span(),
         })
     }
 
-    fn string_value(input: Node) -> Result<AstNode> {
+    fn string_chars(input: Node) -> Result<AstNode> {
         Ok(AstNode {
             kind: AstNodeKind::String(input.as_str().to_owned()),
             span: input.as_span(),
         })
     }
 
+    fn string_value(input: Node) -> Result<AstNode> {
+        Ok(match_nodes!(input.into_children();
+            [string_chars(value)] => value,
+        ))
+    }
+
     fn bool_cte(input: Node) -> Result<AstNode> {
         let value = input
             .as_str()
+            .to_lowercase()
             .parse::<bool>()
-            .map_err(|e| input.error(e))
-            .unwrap();
+            .map_err(|e| input.error(e))?;
         Ok(AstNode {
             kind: AstNodeKind::Bool(value),
             span: input.as_span(),
@@ -201,11 +238,86 @@ impl LanguageParser {
             [expr(expr)] => expr,
             [id(id)] => id,
             [func_call(call)] => call,
+            [field_access(id)] => id,
+            [argv(argv)] => argv,
+            [read_bool(read)] => read,
+            [string_predicate(predicate)] => predicate,
+            [to_string(value)] => value,
+            [random(random)] => random,
+            [random_int(random)] => random,
             [arr_val(id)] => id,
             [dataframe_value_ops(id)] => id,
         ))
     }
 
+    fn argv(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [expr(index)] => AstNode { kind: AstNodeKind::Argv(Box::new(index)), span },
+        ))
+    }
+
+    fn contains(input: Node) -> Result<Operator> {
+        Ok(Operator::Contains)
+    }
+
+    fn starts_with(input: Node) -> Result<Operator> {
+        Ok(Operator::StartsWith)
+    }
+
+    fn ends_with(input: Node) -> Result<Operator> {
+        Ok(Operator::EndsWith)
+    }
+
+    fn string_predicate_key(input: Node) -> Result<Operator> {
+        Ok(match_nodes!(input.into_children();
+            [contains(op)] => op,
+            [starts_with(op)] => op,
+            [ends_with(op)] => op,
+        ))
+    }
+
+    fn string_predicate(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [string_predicate_key(operator), expr(text), expr(pattern)] => {
+                let kind = AstNodeKind::BinaryOperation {
+                    operator,
+                    lhs: Box::new(text),
+                    rhs: Box::new(pattern),
+                };
+                AstNode { kind, span }
+            },
+        ))
+    }
+
+    fn to_string(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [expr(operand)] => {
+                let kind = AstNodeKind::UnaryOperation {
+                    operator: Operator::ToString,
+                    operand: Box::new(operand),
+                };
+                AstNode { kind, span }
+            },
+        ))
+    }
+
+    fn random(input: Node) -> Result<AstNode> {
+        Ok(AstNode::new(AstNodeKind::Random, &input.as_span()))
+    }
+
+    fn random_int(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [expr(low), expr(high)] => {
+                let kind = AstNodeKind::RandomInt { low: Box::new(low), high: Box::new(high) };
+                AstNode { kind, span }
+            },
+        ))
+    }
+
     fn possible_str(input: Node) -> Result<AstNode> {
         Ok(match_nodes!(input.into_children();
             [non_cte(expr)] => expr,
@@ -221,8 +333,48 @@ impl LanguageParser {
         })
     }
 
+    /// The fields of a record are variables named `{record}.{field}`
+    fn field_access(input: Node) -> Result<AstNode> {
+        Ok(AstNode {
+            kind: AstNodeKind::Id(input.as_str().to_owned()),
+            span: input.as_span(),
+        })
+    }
+
     // Expressions
     fn expr(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [implies_term(value)] => value,
+            [implies_term(first), implies_term(rest)..] => {
+                rest.fold(first, |lhs, rhs| {
+                    let kind = AstNodeKind::BinaryOperation {
+                        operator: Operator::Iff,
+                        lhs: Box::new(lhs),
+                        rhs: Box::new(rhs),
+                    };
+                    AstNode { kind, span: span.clone() }
+                })
+            },
+        ))
+    }
+
+    fn implies_term(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [or_term(value)] => value,
+            [or_term(lhs), implies_term(rhs)] => {
+                let kind = AstNodeKind::BinaryOperation {
+                    operator: Operator::Implies,
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                };
+                AstNode { kind, span }
+            },
+        ))
+    }
+
+    fn or_term(input: Node) -> Result<AstNode> {
         let span = input.as_span();
         Ok(match_nodes!(input.into_children();
             [and_term(value)] => value,
@@ -240,8 +392,8 @@ impl LanguageParser {
     fn and_term(input: Node) -> Result<AstNode> {
         let span = input.as_span();
         Ok(match_nodes!(input.into_children();
-            [comp_term(value)] => value,
-            [comp_term(lhs), comp_term(rhs)] => {
+            [not_term(value)] => value,
+            [not_term(lhs), not_term(rhs)] => {
                 let kind = AstNodeKind::BinaryOperation {
                     operator: Operator::And,
                     lhs: Box::new(lhs),
@@ -252,6 +404,17 @@ impl LanguageParser {
         ))
     }
 
+    fn not_term(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [comp_term(value)] => value,
+            [not(operator), not_term(operand)] => {
+                let kind = AstNodeKind::UnaryOperation { operator, operand: Box::new(operand) };
+                AstNode { kind, span }
+            }
+        ))
+    }
+
     fn comp_term(input: Node) -> Result<AstNode> {
         let span = input.as_span();
         Ok(match_nodes!(input.into_children();
@@ -313,13 +476,8 @@ impl LanguageParser {
     }
 
     fn operand(input: Node) -> Result<AstNode> {
-        let span = input.as_span();
         Ok(match_nodes!(input.into_children();
             [operand_value(value)] => value,
-            [not(operator), operand_value(operand)] => {
-                let kind = AstNodeKind::UnaryOperation { operator, operand: Box::new(operand) };
-                AstNode { kind, span }
-            }
         ))
     }
 
@@ -350,17 +508,42 @@ impl LanguageParser {
     fn declare_arr(input: Node) -> Result<AstNode> {
         let span = input.as_span();
         Ok(match_nodes!(input.into_children();
-            [declare_arr_type(data_type), int_cte(dim1)] => {
-                let kind = AstNodeKind::ArrayDeclaration { data_type, dim1: dim1.into(), dim2: None };
+            [declare_arr_type(data_type), expr(dim1)] => {
+                let dim1 = const_dimension(&dim1)?;
+                let kind = AstNodeKind::ArrayDeclaration { data_type, dim1, dim2: None };
                 AstNode {kind, span}
             },
-            [declare_arr_type(data_type), int_cte(dim1), int_cte(dim2)] => {
-                let kind = AstNodeKind::ArrayDeclaration { data_type, dim1: dim1.into(), dim2: Some(dim2.into()) };
+            [declare_arr_type(data_type), expr(dim1), expr(dim2)] => {
+                let (dim1, dim2) = (const_dimension(&dim1)?, const_dimension(&dim2)?);
+                let kind = AstNodeKind::ArrayDeclaration { data_type, dim1, dim2: Some(dim2) };
                 AstNode {kind, span}
             },
         ))
     }
 
+    /// Size of the arrays declared by `fill`, `split` and `columns_of`, which
+    /// are allocated while compiling
+    fn fill_size(input: Node) -> Result<usize> {
+        let size = match_nodes!(input.clone().into_children();
+            [int_cte(size)] => size.const_int(),
+        );
+        match size {
+            Some(size) if size > 0 => Ok(size as usize),
+            Some(size) => Err(input.error(format!("Sizes must be positive, but got {size}"))),
+            None => unreachable!(),
+        }
+    }
+
+    fn fill(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [expr(value), fill_size(size)] => {
+                let kind = AstNodeKind::FilledArray { value: Box::new(value), size };
+                AstNode { kind, span }
+            },
+        ))
+    }
+
     fn list_cte(input: Node) -> Result<AstNode> {
         let span = input.as_span();
         Ok(match_nodes!(input.into_children();
@@ -415,6 +598,34 @@ impl LanguageParser {
         ))
     }
 
+    fn columns_of(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [id(name)] => {
+                let kind = AstNodeKind::ColumnsOf { name: String::from(name), size: COLUMNS_OF_SIZE };
+                AstNode { kind, span }
+            },
+            [id(name), fill_size(size)] => {
+                let kind = AstNodeKind::ColumnsOf { name: String::from(name), size };
+                AstNode { kind, span }
+            },
+        ))
+    }
+
+    fn split(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        let (text, separator, size) = match_nodes!(input.into_children();
+            [expr(text), expr(separator)] => (text, separator, SPLIT_PARTS),
+            [expr(text), expr(separator), fill_size(size)] => (text, separator, size),
+        );
+        let kind = AstNodeKind::Split {
+            text: Box::new(text),
+            separator: Box::new(separator),
+            size,
+        };
+        Ok(AstNode { kind, span })
+    }
+
     fn get_rows(input: Node) -> Result<Operator> {
         Ok(Operator::Rows)
     }
@@ -471,6 +682,26 @@ impl LanguageParser {
         Ok(Operator::Range)
     }
 
+    fn null_count(input: Node) -> Result<Operator> {
+        Ok(Operator::NullCount)
+    }
+
+    fn first(input: Node) -> Result<Operator> {
+        Ok(Operator::First)
+    }
+
+    fn last(input: Node) -> Result<Operator> {
+        Ok(Operator::Last)
+    }
+
+    fn argmin(input: Node) -> Result<Operator> {
+        Ok(Operator::ArgMin)
+    }
+
+    fn argmax(input: Node) -> Result<Operator> {
+        Ok(Operator::ArgMax)
+    }
+
     fn unary_dataframe_key(input: Node) -> Result<Operator> {
         Ok(match_nodes!(input.into_children();
             [average(op)] => op,
@@ -480,6 +711,11 @@ impl LanguageParser {
             [min(op)] => op,
             [max(op)] => op,
             [range(op)] => op,
+            [null_count(op)] => op,
+            [first(op)] => op,
+            [last(op)] => op,
+            [argmin(op)] => op,
+            [argmax(op)] => op,
         ))
     }
 
@@ -542,7 +778,52 @@ impl LanguageParser {
                 let name = String::from(id);
                 let column = Box::new(col);
                 let bins = Box::new(bins);
-                let kind = AstNodeKind::Histogram { name, column, bins };
+                let kind = AstNodeKind::Histogram { name, column, bins, density: None };
+                AstNode { kind, span }
+            },
+            [id(id), possible_str(col), expr(bins), expr(density)] => {
+                let name = String::from(id);
+                let column = Box::new(col);
+                let bins = Box::new(bins);
+                let density = Some(Box::new(density));
+                let kind = AstNodeKind::Histogram { name, column, bins, density };
+                AstNode { kind, span }
+            },
+        ))
+    }
+
+    fn describe(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [id(id)] => {
+                let kind = AstNodeKind::Describe(String::from(id));
+                AstNode { kind, span }
+            },
+        ))
+    }
+
+    fn cast_column(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [id(id), possible_str(col), atomic_types(data_type)] => {
+                let name = String::from(id);
+                let column = Box::new(col);
+                let kind = AstNodeKind::CastColumn { name, column, data_type };
+                AstNode { kind, span }
+            },
+        ))
+    }
+
+    fn show(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [id(id)] => {
+                let kind = AstNodeKind::Show { name: String::from(id), rows: None };
+                AstNode { kind, span }
+            },
+            [id(id), expr(rows)] => {
+                let rows = Some(Box::new(rows));
+                let kind = AstNodeKind::Show { name: String::from(id), rows };
                 AstNode { kind, span }
             },
         ))
@@ -596,6 +877,68 @@ impl LanguageParser {
         ))
     }
 
+    fn repeat_loop(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [expr(count), block_or_statement(statements)] => {
+                let kind = AstNodeKind::Repeat {
+                    count: Box::new(count),
+                    statements,
+                };
+                AstNode {kind, span}
+            },
+        ))
+    }
+
+    fn try_catch(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [block(body), block(handler)] => {
+                AstNode { kind: AstNodeKind::Try { body, handler }, span }
+            },
+        ))
+    }
+
+    fn match_arm(input: Node) -> Result<(AstNode, Vec<AstNode>)> {
+        Ok(match_nodes!(input.into_children();
+            [int_cte(value), block_or_statement(statements)] => (value, statements),
+        ))
+    }
+
+    fn default_arm(input: Node) -> Result<Vec<AstNode>> {
+        Ok(match_nodes!(input.into_children();
+            [block_or_statement(statements)] => statements,
+        ))
+    }
+
+    fn match_statement(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [expr(expr), match_arm(arms)..] => {
+                let kind = AstNodeKind::Match { expr: Box::new(expr), arms: arms.collect(), default: None };
+                AstNode { kind, span }
+            },
+            [expr(expr), match_arm(arms).., default_arm(default)] => {
+                let kind = AstNodeKind::Match { expr: Box::new(expr), arms: arms.collect(), default: Some(default) };
+                AstNode { kind, span }
+            },
+        ))
+    }
+
+    fn for_each(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [id(element), id(array), block_or_statement(statements)] => {
+                let kind = AstNodeKind::ForEach {
+                    name: String::from(element),
+                    array: String::from(array),
+                    statements,
+                };
+                AstNode { kind, span }
+            },
+        ))
+    }
+
     fn for_loop(input: Node) -> Result<AstNode> {
         let span = input.as_span();
         Ok(match_nodes!(input.into_children();
@@ -617,21 +960,76 @@ impl LanguageParser {
     fn assignee(input: Node) -> Result<Box<AstNode>> {
         Ok(match_nodes!(input.into_children();
             [id(id)] => Box::new(id),
+            [field_access(id)] => Box::new(id),
             [arr_val(id)] => Box::new(id),
         ))
     }
 
     fn read(input: Node) -> Result<AstNode> {
-        Ok(AstNode::new(AstNodeKind::Read, &input.as_span()))
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [] => AstNode::new(AstNodeKind::Read(None), &span),
+            [string_value(prompt)] => {
+                let kind = AstNodeKind::Read(Some(String::from(prompt)));
+                AstNode { kind, span }
+            },
+        ))
+    }
+
+    fn read_bool(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [] => AstNode::new(AstNodeKind::ReadBool(None), &span),
+            [string_value(prompt)] => {
+                let kind = AstNodeKind::ReadBool(Some(String::from(prompt)));
+                AstNode { kind, span }
+            },
+        ))
+    }
+
+    fn record_field(input: Node) -> Result<(String, AstNode)> {
+        Ok(match_nodes!(input.into_children();
+            [id(id), expr(value)] => (String::from(id), value),
+        ))
+    }
+
+    fn record_fields(input: Node) -> Result<Vec<(String, AstNode)>> {
+        let mut fields: Vec<(String, AstNode)> = Vec::new();
+        for field in input.into_children() {
+            let (name, value) = Self::record_field(field.clone())?;
+            if fields.iter().any(|(given, _)| *given == name) {
+                return Err(field.error(format!("Field {name} was already given")));
+            }
+            fields.push((name, value));
+        }
+        Ok(fields)
+    }
+
+    fn record_cte(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [id(id)] => {
+                let kind = AstNodeKind::Record { name: String::from(id), fields: Vec::new() };
+                AstNode { kind, span }
+            },
+            [id(id), record_fields(fields)] => {
+                let kind = AstNodeKind::Record { name: String::from(id), fields };
+                AstNode { kind, span }
+            },
+        ))
     }
 
     fn assignment_exp(input: Node) -> Result<AstNode> {
         Ok(match_nodes!(input.into_children();
             [expr(value)] => value,
+            [record_cte(value)] => value,
             [read(value)] => value,
             [declare_arr(value)] => value,
+            [fill(value)] => value,
             [arr_cte(arr)] => arr,
             [read_csv(v)] => v,
+            [columns_of(v)] => v,
+            [split(v)] => v,
         ))
     }
 
@@ -649,6 +1047,27 @@ impl LanguageParser {
         ))
     }
 
+    fn multi_assignment(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [assignee(assignees).., func_call(call)] => {
+                let assignees = assignees.map(|assignee| *assignee).collect();
+                let kind = AstNodeKind::MultiAssignment { assignees, call: Box::new(call) };
+                AstNode { kind, span }
+            },
+        ))
+    }
+
+    fn increment(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [assignee(assignee), step_op(operator)] => {
+                let kind = AstNodeKind::Increment { operator, assignee };
+                AstNode { kind, span }
+            },
+        ))
+    }
+
     fn global_assignment(input: Node) -> Result<AstNode> {
         let span = input.as_span();
         Ok(match_nodes!(input.into_children();
@@ -662,17 +1081,82 @@ impl LanguageParser {
     fn write(input: Node) -> Result<AstNode> {
         let span = input.as_span();
         Ok(match_nodes!(input.into_children();
+            [] => AstNode { kind: AstNodeKind::Write { exprs: vec![], separator: None }, span },
             [exprs(exprs)] => {
-                AstNode { kind: AstNodeKind::Write(exprs), span }
+                AstNode { kind: AstNodeKind::Write { exprs, separator: None }, span }
+            },
+            [exprs(exprs), write_sep(separator)] => {
+                let kind = AstNodeKind::Write { exprs, separator: Some(separator) };
+                AstNode { kind, span }
             },
         ))
     }
 
+    fn print_join(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [string_value(separator)] => {
+                let kind = AstNodeKind::Write { exprs: vec![], separator: Some(String::from(separator)) };
+                AstNode { kind, span }
+            },
+            [string_value(separator), exprs(exprs)] => {
+                let kind = AstNodeKind::Write { exprs, separator: Some(String::from(separator)) };
+                AstNode { kind, span }
+            },
+        ))
+    }
+
+    fn write_sep(input: Node) -> Result<String> {
+        Ok(match_nodes!(input.into_children();
+            [string_value(separator)] => String::from(separator),
+        ))
+    }
+
     fn return_statement(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [] => AstNode::new(AstNodeKind::Return(None), &span),
+            [expr(expr)] => {
+                AstNode { kind: AstNodeKind::Return(Some(Box::new(expr))), span }
+            },
+            [expr(values)..] => {
+                let values = AstNode::new(AstNodeKind::Tuple(values.collect()), &span);
+                AstNode { kind: AstNodeKind::Return(Some(Box::new(values))), span }
+            },
+        ))
+    }
+
+    fn assert_statement(input: Node) -> Result<AstNode> {
         let span = input.as_span();
         Ok(match_nodes!(input.into_children();
             [expr(expr)] => {
-                AstNode { kind: AstNodeKind::Return(Box::new(expr)), span }
+                let kind = AstNodeKind::Assert { expr: Box::new(expr), message: None };
+                AstNode { kind, span }
+            },
+            [expr(expr), string_value(message)] => {
+                let message = Some(String::from(message));
+                let kind = AstNodeKind::Assert { expr: Box::new(expr), message };
+                AstNode { kind, span }
+            },
+        ))
+    }
+
+    fn error_statement(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [string_value(message)] => {
+                let kind = AstNodeKind::Error(String::from(message));
+                AstNode { kind, span }
+            },
+        ))
+    }
+
+    fn append(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [id(name), expr(value)] => {
+                let kind = AstNodeKind::Append { name: String::from(name), value: Box::new(value) };
+                AstNode { kind, span }
             },
         ))
     }
@@ -680,11 +1164,20 @@ impl LanguageParser {
     fn inline_statement(input: Node) -> Result<AstNode> {
         Ok(match_nodes!(input.into_children();
             [assignment(node)] => node,
+            [multi_assignment(node)] => node,
+            [increment(node)] => node,
             [write(node)] => node,
+            [print_join(node)] => node,
             [func_call(node)] => node,
             [return_statement(node)] => node,
+            [assert_statement(node)] => node,
+            [error_statement(node)] => node,
+            [append(node)] => node,
             [plot(node)] => node,
             [histogram(node)] => node,
+            [describe(node)] => node,
+            [show(node)] => node,
+            [cast_column(node)] => node,
         ))
     }
 
@@ -693,7 +1186,11 @@ impl LanguageParser {
             [inline_statement(node)] => node,
             [decision(node)] => node,
             [while_loop(node)] => node,
+            [repeat_loop(node)] => node,
+            [try_catch(node)] => node,
             [for_loop(node)] => node,
+            [for_each(node)] => node,
+            [match_statement(node)] => node,
         ))
     }
 
@@ -703,6 +1200,19 @@ impl LanguageParser {
         ))
     }
 
+    fn body_statement(input: Node) -> Result<AstNode> {
+        Ok(match_nodes!(input.into_children();
+            [function(node)] => node,
+            [statement(node)] => node,
+        ))
+    }
+
+    fn function_block(input: Node) -> Result<Vec<AstNode>> {
+        Ok(match_nodes!(input.into_children();
+            [body_statement(statements)..] => statements.collect(),
+        ))
+    }
+
     fn block_or_statement(input: Node) -> Result<Vec<AstNode>> {
         Ok(match_nodes!(input.into_children();
             [inline_statement(statements)] => vec![statements],
@@ -714,7 +1224,7 @@ impl LanguageParser {
     fn func_arg(input: Node) -> Result<AstNode> {
         let span = input.as_span();
         Ok(match_nodes!(input.into_children();
-            [id(id), atomic_types(arg_type)] => {
+            [id(id), arg_types(arg_type)] => {
                 let kind = AstNodeKind::Argument { arg_type, name: String::from(id) };
                 AstNode { kind, span }
             },
@@ -727,20 +1237,78 @@ impl LanguageParser {
         ))
     }
 
+    fn return_types(input: Node) -> Result<Vec<Types>> {
+        if input.children().count() > MAX_RETURNS {
+            return Err(input.error(format!(
+                "A function can return at most {MAX_RETURNS} values"
+            )));
+        }
+        Ok(match_nodes!(input.into_children();
+            [atomic_types(types)..] => types.collect(),
+        ))
+    }
+
     fn function(input: Node) -> Result<AstNode> {
         let span = input.as_span();
-        Ok(match_nodes!(input.into_children();
-            [id(id), func_args(arguments), types(return_type), block(body)] => {
-                let kind = AstNodeKind::Function {arguments, name: String::from(id), body, return_type};
-                AstNode { kind, span }
+        let (name, arguments, mut return_types, body) = match_nodes!(input.into_children();
+            [id(id), func_args(arguments), types(return_type), function_block(body)] => {
+                (id, arguments, vec![return_type], body)
+            },
+            [id(id), func_args(arguments), return_types(return_types), function_block(body)] => {
+                (id, arguments, return_types, body)
+            },
+            [id(id), types(return_type), function_block(body)] => {
+                (id, Vec::new(), vec![return_type], body)
+            },
+            [id(id), return_types(return_types), function_block(body)] => {
+                (id, Vec::new(), return_types, body)
             },
-            [id(id), types(return_type), block(body)] => {
-                let kind = AstNodeKind::Function {arguments: Vec::new(), name: String::from(id), body, return_type};
+        );
+        let return_type = return_types.remove(0);
+        let kind = AstNodeKind::Function {
+            arguments,
+            name: String::from(name),
+            body,
+            return_type,
+            extra_returns: return_types,
+        };
+        Ok(AstNode { kind, span })
+    }
+
+    fn struct_field(input: Node) -> Result<(String, Types)> {
+        Ok(match_nodes!(input.into_children();
+            [id(id), atomic_types(data_type)] => (String::from(id), data_type),
+        ))
+    }
+
+    fn struct_fields(input: Node) -> Result<Vec<(String, Types)>> {
+        let mut fields: Vec<(String, Types)> = Vec::new();
+        for field in input.into_children() {
+            let (name, data_type) = Self::struct_field(field.clone())?;
+            if fields.iter().any(|(declared, _)| *declared == name) {
+                return Err(field.error(format!("Field {name} was already declared")));
+            }
+            fields.push((name, data_type));
+        }
+        Ok(fields)
+    }
+
+    fn struct_decl(input: Node) -> Result<AstNode> {
+        let span = input.as_span();
+        Ok(match_nodes!(input.into_children();
+            [id(id), struct_fields(fields)] => {
+                let kind = AstNodeKind::RecordDeclaration { name: String::from(id), fields };
                 AstNode { kind, span }
             },
         ))
     }
 
+    fn struct_decls(input: Node) -> Result<Vec<AstNode>> {
+        Ok(match_nodes!(input.into_children();
+            [struct_decl(records)..] => records.collect(),
+        ))
+    }
+
     fn global_assignments(input: Node) -> Result<Vec<AstNode>> {
         Ok(match_nodes!(input.into_children();
             [global_assignment(args)..] => args.collect(),
@@ -750,11 +1318,23 @@ impl LanguageParser {
     fn program(input: Node) -> Result<AstNode> {
         let span = input.as_span();
         Ok(match_nodes!(input.into_children();
-            [global_assignments(nodes), function(functions).., _, block(body), _] => {
+            [struct_decls(records), global_assignments(nodes), function(functions).., _, function_block(mut body), _] => {
+                let mut functions = hoist_all_functions(functions);
+                functions.extend(hoist_functions("main", &mut body));
+                let kind = AstNodeKind::Main {
+                    assignments: nodes,
+                    body: Some(body),
+                    functions,
+                    records,
+                };
+                AstNode { kind, span }
+            },
+            [struct_decls(records), global_assignments(nodes), function(functions).., _] => {
                 let kind = AstNodeKind::Main {
                     assignments: nodes,
-                    body,
-                    functions: functions.collect(),
+                    body: None,
+                    functions: hoist_all_functions(functions),
+                    records,
                 };
                 AstNode { kind, span }
             },
@@ -762,7 +1342,63 @@ impl LanguageParser {
     }
 }
 
-pub fn parse(source: &str, debug: bool) -> Result<AstNode> {
+/// Arrays are allocated while compiling, so their dimensions must be positive
+/// integer expressions that can be evaluated by then
+fn const_dimension(node: &AstNode) -> Result<usize> {
+    let message = match node.const_int() {
+        Some(value) if value > 0 => return Ok(value as usize),
+        Some(value) => format!("Array dimensions must be positive, but got {value}"),
+        None => "Array dimensions must be integer constants".to_owned(),
+    };
+    let variant = ErrorVariant::CustomError { message };
+    Err(Error::new_from_span(variant, node.span.clone()))
+}
+
+/// Moves the functions declared inside `body` next to the top-level ones,
+/// named `{scope}::{name}`, and points the calls made within the enclosing
+/// function to them. Each hoisted function comes before the ones that use it.
+fn hoist_functions<'a>(scope: &str, body: &mut Vec<AstNode<'a>>) -> Vec<AstNode<'a>> {
+    let (nested, statements): (Vec<_>, Vec<_>) = std::mem::take(body)
+        .into_iter()
+        .partition(|node| matches!(node.kind, AstNodeKind::Function { .. }));
+    *body = statements;
+    let mut renames = HashMap::new();
+    let mut hoisted = Vec::new();
+    for mut function in nested {
+        if let AstNodeKind::Function {
+            name,
+            arguments,
+            body,
+            ..
+        } = &mut function.kind
+        {
+            let scoped_name = format!("{scope}::{name}");
+            renames.insert((name.clone(), arguments.len()), scoped_name.clone());
+            hoisted.extend(hoist_functions(&scoped_name, body));
+            *name = scoped_name;
+        }
+        hoisted.push(function);
+    }
+    body.iter_mut()
+        .chain(&mut hoisted)
+        .for_each(|node| node.rename_calls(&renames));
+    hoisted
+}
+
+fn hoist_all_functions<'a>(functions: impl Iterator<Item = AstNode<'a>>) -> Vec<AstNode<'a>> {
+    functions
+        .flat_map(|mut function| {
+            let mut hoisted = match &mut function.kind {
+                AstNodeKind::Function { name, body, .. } => hoist_functions(&name.clone(), body),
+                _ => unreachable!(),
+            };
+            hoisted.push(function);
+            hoisted
+        })
+        .collect()
+}
+
+pub fn parse(source: &str, debug: bool) -> Result<AstNode<'_>> {
     let inputs = LanguageParser::parse_with_userdata(Rule::program, source, debug)?;
     // There should be a single root node in the parsed tree
     let input = inputs.single()?;
@@ -6,6 +6,9 @@ fn valid_files() {
     let paths = read_dir("src/examples/valid").unwrap();
     for path in paths {
         let file_path = path.expect("File must exist").path();
+        if file_path.extension() != Some("ra".as_ref()) {
+            continue;
+        }
         let file = file_path.to_str().unwrap();
         if file == "examples/valid/complete.ra" {
             continue;
@@ -18,9 +21,291 @@ fn valid_files() {
 }
 
 #[test]
-fn invalid_file() {
-    let filename = "src/examples/invalid/syntax/syntax-error.ra";
-    let program = std::fs::read_to_string(filename).expect(filename);
-    let res = parse(&program, true);
-    assert!(res.is_err());
+fn invalid_files() {
+    for path in read_dir("src/examples/invalid/syntax").unwrap() {
+        let file_path = path.expect("File must exist").path();
+        let file = file_path.to_str().unwrap();
+        let program = std::fs::read_to_string(file).expect(file);
+        println!("Testing {:?}", file);
+        assert!(parse(&program, true).is_err());
+    }
+}
+
+fn parse_expr_debug(expr: &str) -> String {
+    let program = format!("func main(): void {{ a = {expr}; }}");
+    let ast = parse(&program, false).unwrap();
+    format!("{:?}", ast.children()[0].children()[1])
+}
+
+#[test]
+fn not_binds_looser_than_comparisons() {
+    assert_eq!(
+        parse_expr_debug("NOT 1 == 2"),
+        "Unary(Not, BinaryOperation(Eq, Integer(1), Integer(2)))"
+    );
+    assert_eq!(
+        parse_expr_debug("NOT 1 < 2"),
+        "Unary(Not, BinaryOperation(Lt, Integer(1), Integer(2)))"
+    );
+}
+
+#[test]
+fn not_binds_tighter_than_and_or() {
+    assert_eq!(
+        parse_expr_debug("NOT true AND false"),
+        "BinaryOperation(And, Unary(Not, Bool(true)), Bool(false))"
+    );
+    assert_eq!(
+        parse_expr_debug("true OR NOT false"),
+        "BinaryOperation(Or, Bool(true), Unary(Not, Bool(false)))"
+    );
+    assert_eq!(
+        parse_expr_debug("(NOT 1) == 2"),
+        "BinaryOperation(Eq, Unary(Not, Integer(1)), Integer(2))"
+    );
+}
+
+#[test]
+fn fill_size_must_be_a_positive_constant() {
+    let program = |size: &str| format!("func main(): void {{ a = fill(0, {size}); }}");
+    assert!(parse(&program("3"), false).is_ok());
+    assert!(parse(&program("0"), false).is_err());
+    assert!(parse(&program("-1"), false).is_err());
+    assert!(parse(&program("n"), false).is_err());
+}
+
+#[test]
+fn columns_of_size_is_optional_and_positive() {
+    let program = |args: &str| format!("func main(): void {{ a = columns_of({args}); }}");
+    assert!(parse(&program("data"), false).is_ok());
+    assert!(parse(&program("data, 4"), false).is_ok());
+    let error = parse(&program("data, -2"), false).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("Sizes must be positive, but got -2"));
+    assert!(parse(&program("data, 0"), false).is_err());
+}
+
+#[test]
+fn split_size_must_be_positive() {
+    let program =
+        |size: &str| format!("func main(): void {{ a = split(\"a,b\", \",\", {size}); }}");
+    assert!(parse(&program("3"), false).is_ok());
+    let error = parse(&program("-3"), false).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("Sizes must be positive, but got -3"));
+    assert!(parse(&program("0"), false).is_err());
+}
+
+#[test]
+fn overflowing_int_is_a_syntax_error() {
+    let program = "func main(): void { a = 9223372036854775808; }";
+    let error = parse(program, false).unwrap_err();
+    assert_eq!(
+        error.line_col,
+        pest::error::LineColLocation::Span((1, 25), (1, 44))
+    );
+    assert!(error.to_string().contains("number too large"));
+    assert!(parse("func main(): void { a = 9223372036854775807; }", false).is_ok());
+}
+
+#[test]
+fn underscores_between_digits() {
+    assert_eq!(parse_expr_debug("1_000"), "Integer(1000)");
+    assert_eq!(parse_expr_debug("-1_000_000"), "Integer(-1000000)");
+    assert_eq!(parse_expr_debug("1_000.5"), "Float(1000.5)");
+    assert_eq!(parse_expr_debug("0.000_1"), "Float(0.0001)");
+    let program = |value: &str| format!("func main(): void {{ a = {value}; }}");
+    for value in ["_1000", "1000_", "1__000", "1_.5", "1._5", "1.5_"] {
+        assert!(parse(&program(value), false).is_err(), "{value}");
+    }
+}
+
+#[test]
+fn increment_statements() {
+    let program = "func main(): void { i = 0; i++; arr[i]--; }";
+    let ast = parse(program, false).unwrap();
+    let body = ast.children();
+    assert_eq!(format!("{:?}", body[1]), "Increment(Inc, Id(i))");
+    assert_eq!(
+        format!("{:?}", body[2]),
+        "Increment(Dec, ArrayVal(arr, Id(i), None))"
+    );
+    assert!(parse("func main(): void { i+++; }", false).is_err());
+    assert!(parse("func main(): void { 1++; }", false).is_err());
+}
+
+#[test]
+fn dataframe_operation_aliases() {
+    for (alias, operator) in [
+        ("average", "Average"),
+        ("mean", "Average"),
+        ("avg", "Average"),
+        ("std", "Std"),
+        ("stddev", "Std"),
+    ] {
+        let expr = format!("{alias}(data, \"age\")");
+        let expected = format!("UnaryDataframeOp({operator}, data, String(age))");
+        assert_eq!(parse_expr_debug(&expr), expected, "{alias}");
+    }
+    for alias in ["mean", "avg", "stddev"] {
+        assert_eq!(parse_expr_debug(alias), format!("Id({alias})"));
+    }
+    let program = "func avg(x: int): int { return x; }
+    func main(): void { mean = avg(2); stddev = mean; }";
+    assert!(parse(program, false).is_ok());
+}
+
+#[test]
+fn for_each_loops() {
+    let program = "func main(): void { for x in arr { print(x); } for item in items print(item); }";
+    let ast = parse(program, false).unwrap();
+    let body = ast.children();
+    assert_eq!(
+        format!("{:?}", body[0]),
+        "ForEach(x, arr, [Write([Id(x)])])"
+    );
+    assert_eq!(
+        format!("{:?}", body[1]),
+        "ForEach(item, items, [Write([Id(item)])])"
+    );
+    // Identifiers can still start with `in`
+    assert!(parse("func main(): void { index = 1; inside = 2; }", false).is_ok());
+    assert!(parse("func main(): void { for in in arr { } }", false).is_err());
+}
+
+#[test]
+fn trailing_commas() {
+    assert_eq!(
+        parse_expr_debug("foo(1, 2,)"),
+        "FunctionCall(foo, [Integer(1), Integer(2)])"
+    );
+    assert_eq!(
+        parse_expr_debug("[1, 2, 3,]"),
+        "Array([Integer(1), Integer(2), Integer(3)])"
+    );
+    assert_eq!(
+        parse_expr_debug("[[1, 2,], [3, 4],]"),
+        "Array([Array([Integer(1), Integer(2)]), Array([Integer(3), Integer(4)])])"
+    );
+    let program = "func foo(a: int, b: int,): void {} func main(): void { print(1, 2,); print(1, sep=\"-\"); }";
+    assert!(parse(program, false).is_ok());
+    assert!(parse("func main(): void { foo(,); }", false).is_err());
+    assert!(parse("func main(): void { a = [1,,]; }", false).is_err());
+}
+
+#[test]
+fn implies_and_iff_bind_looser_than_or() {
+    assert_eq!(
+        parse_expr_debug("a OR b IMPLIES c"),
+        "BinaryOperation(Implies, BinaryOperation(Or, Id(a), Id(b)), Id(c))"
+    );
+    assert_eq!(
+        parse_expr_debug("a IMPLIES b IMPLIES c"),
+        "BinaryOperation(Implies, Id(a), BinaryOperation(Implies, Id(b), Id(c)))"
+    );
+    assert_eq!(
+        parse_expr_debug("a IFF b IMPLIES c IFF d"),
+        "BinaryOperation(Iff, BinaryOperation(Iff, Id(a), BinaryOperation(Implies, Id(b), Id(c))), Id(d))"
+    );
+}
+
+#[test]
+fn python_style_booleans() {
+    assert_eq!(parse_expr_debug("True"), "Bool(true)");
+    assert_eq!(parse_expr_debug("False"), "Bool(false)");
+    assert_eq!(
+        parse_expr_debug("True AND false"),
+        "BinaryOperation(And, Bool(true), Bool(false))"
+    );
+    assert_eq!(parse_expr_debug("TRUE"), "Id(TRUE)");
+    assert!(parse("func main(): void { True = 1; }", false).is_err());
+    assert_eq!(parse_expr_debug("Trueish"), "Id(Trueish)");
+}
+
+#[test]
+fn structs_and_field_access() {
+    let program = "struct Point { x int, y float } func main(): void { p = Point { x: 1, y: 2.5, }; p.x = p.y; }";
+    let ast = parse(program, false).unwrap();
+    let children = ast.children();
+    assert_eq!(
+        format!("{:?}", children[0]),
+        "RecordDeclaration(Point, [(\"x\", Int), (\"y\", Float)])"
+    );
+    assert_eq!(
+        format!("{:?}", children[1].children()[1]),
+        "Record(Point, [(\"x\", Integer(1)), (\"y\", Float(2.5))])"
+    );
+    assert_eq!(format!("{:?}", children[2].children()[0]), "Id(p.x)");
+    assert_eq!(format!("{:?}", children[2].children()[1]), "Id(p.y)");
+    assert!(parse("struct P { x int, x float } func main(): void {}", false).is_err());
+    assert!(parse(
+        "struct P { x int } func main(): void { p = P { x: 1, x: 2 }; }",
+        false
+    )
+    .is_err());
+    assert!(parse("struct P { x int[] } func main(): void {}", false).is_err());
+}
+
+#[test]
+fn array_dimensions_are_folded() {
+    assert_eq!(
+        parse_expr_debug("declare_arr<int>(2 * (3 + 1))"),
+        parse_expr_debug("declare_arr<int>(8)")
+    );
+    assert_eq!(
+        parse_expr_debug("declare_arr<float>(6 / 2, 10 - 8)"),
+        parse_expr_debug("declare_arr<float>(3, 2)")
+    );
+    let error = parse(
+        "func main(): void { n = 2; a = declare_arr<int>(n + 1); }",
+        false,
+    );
+    assert!(error
+        .unwrap_err()
+        .to_string()
+        .contains("Array dimensions must be integer constants"));
+    let error = parse(
+        "func main(): void { a = declare_arr<int>(3, 1 - 1); }",
+        false,
+    );
+    let message = error.unwrap_err().to_string();
+    assert!(message.contains("Array dimensions must be positive, but got 0"));
+    assert!(message.contains("^---^"));
+    assert!(parse("func main(): void { a = declare_arr<int>(1 / 0); }", false).is_err());
+}
+
+#[test]
+fn several_return_values() {
+    let program = "func f(): (int, float) { return 1, 2.5; } func main(): void { a, b = f(); }";
+    let ast = parse(program, false).unwrap();
+    let children = ast.children();
+    let function = children[0];
+    assert!(format!("{function:?}").starts_with("Function(f, [Int, Float], [], ["));
+    assert_eq!(
+        format!("{:?}", function.children()[0]),
+        "Return(Tuple([Integer(1), Float(2.5)]))"
+    );
+    assert_eq!(
+        format!("{:?}", children[1]),
+        "MultiAssignment([Id(a), Id(b)], FunctionCall(f, []))"
+    );
+    let error = parse(
+        "func f(): (int, int, int, int, int) { return 1, 2, 3, 4, 5; } func main(): void {}",
+        false,
+    );
+    assert!(error
+        .unwrap_err()
+        .to_string()
+        .contains("A function can return at most 4 values"));
+}
+
+#[test]
+fn keywords_sharing_a_prefix_are_reserved() {
+    for keyword in ["to_string", "to", "input", "in", "printjoin"] {
+        let program = format!("func main(): void {{ {keyword} = 1; }}");
+        assert!(parse(&program, false).is_err(), "{keyword}");
+    }
+    assert_eq!(parse_expr_debug("to_str"), "Id(to_str)");
 }
@@ -0,0 +1,77 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    address::{AddressManager, ConstantMemory, PointerMemory},
+    dir_func::function::Function,
+    VERSION,
+};
+
+use super::{quadruple::Quadruple, quadruple_manager::QuadrupleManager};
+
+/// Everything the VM needs from a compiled program, so it can be stored and
+/// run later without parsing it again
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Bytecode {
+    /// Compiler that produced it, as the quads change between versions
+    pub version: String,
+    pub constants: ConstantMemory,
+    pub functions: Vec<Function>,
+    pub global_addresses: AddressManager,
+    pub pointer_memory: PointerMemory,
+    pub quads: Vec<Quadruple>,
+    /// Source line of each quad
+    pub quad_lines: Vec<Option<usize>>,
+}
+
+impl From<&QuadrupleManager> for Bytecode {
+    fn from(quad_manager: &QuadrupleManager) -> Self {
+        let mut functions: Vec<_> = quad_manager.dir_func.functions.values().cloned().collect();
+        functions.sort_by_key(|function| function.first_quad);
+        let quad_lines = (0..quad_manager.quad_list.len())
+            .map(|quad_pos| quad_manager.source_line(quad_pos))
+            .collect();
+        Self {
+            version: VERSION.to_owned(),
+            constants: quad_manager.memory.clone(),
+            functions,
+            global_addresses: quad_manager.dir_func.global_fn.addresses.clone(),
+            pointer_memory: quad_manager.pointer_memory.clone(),
+            quads: quad_manager.quad_list.clone(),
+            quad_lines,
+        }
+    }
+}
+
+impl Bytecode {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Compiled programs are always serializable")
+    }
+
+    /// Reads bytecode written by `to_json`, rejecting the one produced by
+    /// another version of the compiler
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let bytecode: Self =
+            serde_json::from_str(json).map_err(|error| format!("Invalid bytecode: {error}"))?;
+        if bytecode.version != VERSION {
+            return Err(format!(
+                "Bytecode compiled by raoul {}, it can't be run by raoul {VERSION}",
+                bytecode.version
+            ));
+        }
+        match bytecode.find_main() {
+            Some(_) => Ok(bytecode),
+            None => Err("Bytecode has no main function".to_owned()),
+        }
+    }
+
+    fn find_main(&self) -> Option<&Function> {
+        self.functions
+            .iter()
+            .find(|function| function.name == "main" && function.args.is_empty())
+    }
+
+    pub fn main_function(&self) -> &Function {
+        self.find_main()
+            .expect("Programs without main are rejected while compiling")
+    }
+}
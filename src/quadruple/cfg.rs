@@ -0,0 +1,99 @@
+use std::{collections::BTreeSet, fmt::Write, ops::Range};
+
+use crate::enums::Operator;
+
+use super::quadruple_manager::QuadrupleManager;
+
+/// Whether the quad after this operator may be executed next
+fn falls_through(operator: Operator) -> bool {
+    !matches!(
+        operator,
+        Operator::Goto | Operator::Return | Operator::EndProc | Operator::End | Operator::Abort
+    )
+}
+
+impl QuadrupleManager {
+    /// Splits the quads into basic blocks. A block starts at the first quad,
+    /// at every jump target, at every function entry and right after any
+    /// jump or exit, and runs until the next one starts.
+    pub fn basic_blocks(&self) -> Vec<Range<usize>> {
+        let quads = self.quads();
+        let mut leaders = BTreeSet::from([0]);
+        for (i, quad) in quads.iter().enumerate() {
+            match quad.operator {
+                Operator::Goto | Operator::GotoF | Operator::Try => {
+                    leaders.insert(quad.res.unwrap().raw());
+                    leaders.insert(i + 1);
+                }
+                Operator::GoSub => {
+                    leaders.insert(quad.op_1.unwrap().raw());
+                }
+                Operator::Return | Operator::EndProc | Operator::End | Operator::Abort => {
+                    leaders.insert(i + 1);
+                }
+                _ => (),
+            }
+        }
+        let leaders: Vec<_> = leaders.into_iter().filter(|&i| i < quads.len()).collect();
+        let ends = leaders.iter().skip(1).copied().chain(Some(quads.len()));
+        leaders
+            .iter()
+            .zip(ends)
+            .map(|(&start, end)| start..end)
+            .collect()
+    }
+
+    /// Control-flow graph of the quads, in Graphviz DOT format. Calls are
+    /// drawn as dashed edges to the entry of the called function.
+    pub fn to_dot(&self) -> String {
+        let quads = self.quads();
+        let blocks = self.basic_blocks();
+        let block_of = |quad: usize| blocks.iter().position(|b| b.contains(&quad)).unwrap();
+        let mut dot =
+            String::from("digraph cfg {\n    node [shape=box, fontname=\"monospace\"];\n");
+        for (i, block) in blocks.iter().enumerate() {
+            let label: String = block
+                .clone()
+                .map(|pos| format!("{pos:<4} - {:?}\\l", quads[pos]))
+                .collect();
+            writeln!(dot, "    b{i} [label=\"{label}\"];").unwrap();
+        }
+        for (i, block) in blocks.iter().enumerate() {
+            for quad in &quads[block.clone()] {
+                if quad.operator == Operator::GoSub {
+                    let callee = block_of(quad.op_1.unwrap().raw());
+                    writeln!(dot, "    b{i} -> b{callee} [style=dashed, label=\"call\"];").unwrap();
+                }
+            }
+            let last = quads[block.end - 1];
+            let next = (block.end < quads.len()).then(|| i + 1);
+            match last.operator {
+                Operator::Goto => {
+                    writeln!(dot, "    b{i} -> b{};", block_of(last.res.unwrap().raw())).unwrap();
+                }
+                Operator::GotoF => {
+                    let target = block_of(last.res.unwrap().raw());
+                    writeln!(dot, "    b{i} -> b{target} [label=\"false\"];").unwrap();
+                    if let Some(next) = next {
+                        writeln!(dot, "    b{i} -> b{next} [label=\"true\"];").unwrap();
+                    }
+                }
+                Operator::Try => {
+                    let target = block_of(last.res.unwrap().raw());
+                    writeln!(dot, "    b{i} -> b{target} [label=\"catch\"];").unwrap();
+                    if let Some(next) = next {
+                        writeln!(dot, "    b{i} -> b{next};").unwrap();
+                    }
+                }
+                operator if falls_through(operator) => {
+                    if let Some(next) = next {
+                        writeln!(dot, "    b{i} -> b{next};").unwrap();
+                    }
+                }
+                _ => (),
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
@@ -1,3 +1,7 @@
+pub mod bytecode;
+pub mod cfg;
+pub mod operand;
+pub mod optimizer;
 #[allow(clippy::module_inception)]
 pub mod quadruple;
 #[allow(clippy::module_name_repetitions)]
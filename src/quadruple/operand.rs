@@ -0,0 +1,61 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::address::{Address, Scope, DATAFRAME_ADDRESS};
+
+/// QuadOperand of a quadruple
+#[derive(Clone, Copy, PartialEq, Hash, Eq, Serialize, Deserialize)]
+pub enum QuadOperand {
+    /// Memory address of a value
+    Address(usize),
+    /// Plain number such as a jump target, a function size or a parameter
+    /// position
+    Literal(usize),
+}
+
+impl QuadOperand {
+    /// The address or number the operand holds, as the VM uses it
+    pub fn raw(self) -> usize {
+        match self {
+            QuadOperand::Address(value) | QuadOperand::Literal(value) => value,
+        }
+    }
+}
+
+impl Address for QuadOperand {
+    fn is_temp_address(&self) -> bool {
+        match self {
+            QuadOperand::Address(address) => address.is_temp_address(),
+            QuadOperand::Literal(_) => false,
+        }
+    }
+
+    fn is_pointer_address(&self) -> bool {
+        match self {
+            QuadOperand::Address(address) => address.is_pointer_address(),
+            QuadOperand::Literal(_) => false,
+        }
+    }
+}
+
+impl fmt::Debug for QuadOperand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = match *self {
+            QuadOperand::Address(DATAFRAME_ADDRESS) => "DF".to_owned(),
+            QuadOperand::Address(address) => {
+                let scope = Scope::from(address);
+                let prefix = match scope {
+                    Scope::Global => 'G',
+                    Scope::Local => 'L',
+                    Scope::Temp => 'T',
+                    Scope::Constant => 'C',
+                    Scope::Pointer => 'P',
+                };
+                format!("{prefix}{}", address - scope.start())
+            }
+            QuadOperand::Literal(value) => value.to_string(),
+        };
+        f.pad(&value)
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use crate::{
+    address::{address_type, Address},
+    enums::{Operator, Types},
+};
+
+use super::{operand::QuadOperand, quadruple::Quadruple, quadruple_manager::QuadrupleManager};
+
+/// Whether the operator computes its result only from its operands, without
+/// touching anything else
+fn is_pure(operator: Operator) -> bool {
+    matches!(
+        operator,
+        Operator::Not
+            | Operator::Or
+            | Operator::And
+            | Operator::Implies
+            | Operator::Iff
+            | Operator::Gte
+            | Operator::Lte
+            | Operator::Gt
+            | Operator::Lt
+            | Operator::Eq
+            | Operator::Ne
+            | Operator::Sum
+            | Operator::Minus
+            | Operator::Times
+            | Operator::Div
+            | Operator::Contains
+            | Operator::StartsWith
+            | Operator::EndsWith
+            | Operator::ToString
+    )
+}
+
+type Expression = (Operator, Option<QuadOperand>, Option<QuadOperand>);
+
+fn reads_pointer(quad: &Quadruple) -> bool {
+    [quad.op_1, quad.op_2]
+        .into_iter()
+        .flatten()
+        .any(|operand| operand.is_pointer_address())
+}
+
+/// Whether the operand is a bool stored directly in memory. Ints can be
+/// negated as well, but `NOT NOT 5` is `true` rather than `5`.
+fn is_bool(operand: QuadOperand) -> bool {
+    match operand {
+        QuadOperand::Address(address) if !address.is_pointer_address() => {
+            address_type(address) == Types::Bool
+        }
+        _ => false,
+    }
+}
+
+impl QuadrupleManager {
+    /// Common-subexpression elimination inside each basic block. A pure
+    /// quad computing the same as an earlier one becomes a copy of the
+    /// earlier result, as long as neither its operands nor that result were
+    /// written in between. Negating the negation of a bool becomes a copy of
+    /// the bool, under the same conditions. Quads are rewritten in place, so
+    /// jump targets stay valid.
+    pub fn optimize(&mut self) {
+        for block in self.basic_blocks() {
+            let mut available: HashMap<Expression, QuadOperand> = HashMap::new();
+            // Result of a `NOT` to the bool it negated
+            let mut negations: HashMap<QuadOperand, QuadOperand> = HashMap::new();
+            for quad in &mut self.quad_list[block] {
+                let res = match quad.res {
+                    Some(res) if !res.is_pointer_address() => res,
+                    None if matches!(quad.operator, Operator::Print | Operator::PrintNl) => {
+                        continue;
+                    }
+                    _ => {
+                        available.clear();
+                        negations.clear();
+                        continue;
+                    }
+                };
+                let negated = quad.op_1.and_then(|op_1| negations.get(&op_1));
+                if let (Operator::Not, Some(&original)) = (quad.operator, negated) {
+                    *quad = Quadruple::new(Operator::Assignment, Some(original), None, Some(res));
+                }
+                negations.retain(|negation, original| *negation != res && *original != res);
+                match (quad.operator, quad.op_1) {
+                    (Operator::Not, Some(op_1)) if is_bool(op_1) && op_1 != res => {
+                        negations.insert(res, op_1);
+                    }
+                    _ => (),
+                }
+                let expression = (quad.operator, quad.op_1, quad.op_2);
+                let pure = is_pure(quad.operator) && !reads_pointer(quad);
+                match available.get(&expression) {
+                    Some(&previous) if pure => {
+                        *quad =
+                            Quadruple::new(Operator::Assignment, Some(previous), None, Some(res));
+                    }
+                    _ if pure || quad.operator == Operator::Assignment => (),
+                    _ => {
+                        available.clear();
+                        negations.clear();
+                        continue;
+                    }
+                }
+                available.retain(|(_, op_1, op_2), result| {
+                    *result != res && *op_1 != Some(res) && *op_2 != Some(res)
+                });
+                let overwrites_operand = expression.1 == Some(res) || expression.2 == Some(res);
+                if pure && !overwrites_operand {
+                    available.entry(expression).or_insert(res);
+                }
+            }
+        }
+    }
+}
@@ -1,28 +1,32 @@
 use std::fmt;
 
+use serde::{Deserialize, Serialize};
+
 use crate::enums::Operator;
 
-#[derive(Clone, Copy, PartialEq, Hash, Eq)]
+use super::operand::QuadOperand::{self, Address};
+
+#[derive(Clone, Copy, PartialEq, Hash, Eq, Serialize, Deserialize)]
 pub struct Quadruple {
     pub operator: Operator,
-    pub op_1: Option<usize>,
-    pub op_2: Option<usize>,
-    pub res: Option<usize>,
+    pub op_1: Option<QuadOperand>,
+    pub op_2: Option<QuadOperand>,
+    pub res: Option<QuadOperand>,
 }
 
 impl Quadruple {
-    fn format_address(option: Option<usize>) -> String {
+    fn format_operand(option: Option<QuadOperand>) -> String {
         match option {
             None => "-".to_owned(),
-            Some(address) => address.to_string(),
+            Some(operand) => format!("{operand:?}"),
         }
     }
 
     pub fn new(
         operator: Operator,
-        op_1: Option<usize>,
-        op_2: Option<usize>,
-        res: Option<usize>,
+        op_1: Option<QuadOperand>,
+        op_2: Option<QuadOperand>,
+        res: Option<QuadOperand>,
     ) -> Self {
         Quadruple {
             operator,
@@ -37,23 +41,28 @@ impl Quadruple {
     }
 
     pub fn new_arg(operator: Operator, op_1: usize) -> Self {
-        Self::new(operator, Some(op_1), None, None)
+        Self::new(operator, Some(Address(op_1)), None, None)
     }
 
     pub fn new_res(operator: Operator, res: usize) -> Self {
-        Self::new(operator, None, None, Some(res))
+        Self::new(operator, None, None, Some(Address(res)))
     }
 
     pub fn new_un(operator: Operator, op_1: usize, res: usize) -> Self {
-        Self::new(operator, Some(op_1), None, Some(res))
+        Self::new(operator, Some(Address(op_1)), None, Some(Address(res)))
     }
 
     pub fn new_args(operator: Operator, op_1: usize, op_2: usize) -> Self {
-        Self::new(operator, Some(op_1), Some(op_2), None)
+        Self::new(operator, Some(Address(op_1)), Some(Address(op_2)), None)
     }
 
     pub fn new_com(operator: Operator, op_1: usize, op_2: usize, res: usize) -> Self {
-        Self::new(operator, Some(op_1), Some(op_2), Some(res))
+        Self::new(
+            operator,
+            Some(Address(op_1)),
+            Some(Address(op_2)),
+            Some(Address(res)),
+        )
     }
 }
 
@@ -63,9 +72,9 @@ impl fmt::Debug for Quadruple {
             f,
             "{} {:5} {:5} {}",
             self.operator,
-            Quadruple::format_address(self.op_1),
-            Quadruple::format_address(self.op_2),
-            Quadruple::format_address(self.res),
+            Quadruple::format_operand(self.op_1),
+            Quadruple::format_operand(self.op_2),
+            Quadruple::format_operand(self.res),
         )
     }
 }
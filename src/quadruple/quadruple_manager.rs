@@ -1,68 +1,95 @@
-use std::fmt;
+use std::{collections::BTreeMap, fmt};
 
 use crate::{
-    address::{Address, ConstantMemory, GenericAddressManager, PointerMemory},
+    address::{Address, ConstantMemory, GenericAddressManager, PointerMemory, Scope},
     ast::{ast_kind::AstNodeKind, AstNode, BoxedNode},
     dir_func::{
-        function::{Function, VariablesTable},
-        variable::Variable,
+        function::{Function, FunctionKey, VariablesTable},
+        variable::{return_slot_name, undeclared_kind, Variable},
         variable_value::VariableValue,
         DirFunc,
     },
     enums::{Operator, Types},
     error::{error_kind::RaoulErrorKind, RaoulError, Results},
-    quadruple::quadruple::Quadruple,
+    quadruple::{
+        operand::QuadOperand::{self, Literal},
+        quadruple::Quadruple,
+    },
 };
 
 #[derive(PartialEq, Debug)]
 pub struct QuadrupleManager {
-    function_name: String,
+    /// Compilation stops at the first error instead of collecting all of them
+    fail_fast: bool,
+    function_key: FunctionKey,
     jump_list: Vec<usize>,
     missing_return: bool,
     pub dir_func: DirFunc,
     pub memory: ConstantMemory,
     pub pointer_memory: PointerMemory,
     pub quad_list: Vec<Quadruple>,
+    /// Line number and text of the statements that start at each quad
+    source_lines: BTreeMap<usize, Vec<(usize, String)>>,
 }
 
 pub type Operand = (usize, Types);
 
-fn safe_address<'a, T>(option: Option<T>, node: &AstNode<'a>) -> Results<'a, T> {
+fn safe_address<'a, T>(option: Option<T>, data_type: Types, node: &AstNode<'a>) -> Results<'a, T> {
     match option {
         Some(value) => Ok(value),
-        None => Err(vec![RaoulError::new(node, RaoulErrorKind::MemoryExceded)]),
+        None => {
+            let kind = RaoulErrorKind::MemoryExceded {
+                data_type,
+                requested: 1,
+            };
+            Err(vec![RaoulError::new(node, kind)])
+        }
     }
 }
 
 impl QuadrupleManager {
-    pub fn new(dir_func: DirFunc) -> QuadrupleManager {
+    pub fn new(dir_func: DirFunc, fail_fast: bool) -> QuadrupleManager {
         QuadrupleManager {
             dir_func,
-            function_name: "".to_owned(),
+            fail_fast,
+            function_key: ("".to_owned(), 0),
             jump_list: Vec::new(),
             memory: ConstantMemory::new(),
             missing_return: false,
             pointer_memory: PointerMemory::new(),
             quad_list: Vec::new(),
+            source_lines: BTreeMap::new(),
         }
     }
 
+    /// Generated quadruples, in execution order
+    #[inline]
+    pub fn quads(&self) -> &[Quadruple] {
+        &self.quad_list
+    }
+
+    /// Line of the statement the quad at `quad_pos` was generated from
+    pub fn source_line(&self, quad_pos: usize) -> Option<usize> {
+        let (_, lines) = self.source_lines.range(..=quad_pos).next_back()?;
+        lines.last().map(|(line, _)| *line)
+    }
+
     #[inline]
     pub fn clear_variables(&mut self) {
         self.dir_func.clear_variables();
     }
 
     #[inline]
-    fn get_function(&self, name: &str) -> &Function {
+    fn get_function(&self, key: &FunctionKey) -> &Function {
         self.dir_func
             .functions
-            .get(name)
-            .expect(&self.function_name)
+            .get(key)
+            .expect(&self.function_key.0)
     }
 
     #[inline]
     fn function(&self) -> &Function {
-        self.get_function(&self.function_name)
+        self.get_function(&self.function_key)
     }
 
     #[inline]
@@ -86,10 +113,7 @@ impl QuadrupleManager {
 
     #[inline]
     fn function_mut(&mut self) -> &mut Function {
-        self.dir_func
-            .functions
-            .get_mut(&self.function_name)
-            .unwrap()
+        self.dir_func.functions.get_mut(&self.function_key).unwrap()
     }
 
     #[inline]
@@ -101,22 +125,29 @@ impl QuadrupleManager {
 
     #[inline]
     fn safe_add_temp<'a>(&mut self, data_type: Types, node: &AstNode<'a>) -> Results<'a, usize> {
-        safe_address(self.add_temp(data_type), node)
+        safe_address(self.add_temp(data_type), data_type, node)
     }
 
-    fn safe_remove_temp_address(&mut self, operand: Option<usize>) {
-        if !operand.is_temp_address() {
+    fn safe_remove_temp_address(&mut self, address: usize) {
+        if !address.is_temp_address() {
             return;
         }
-        self.function_mut()
-            .temp_addresses
-            .release_address(operand.unwrap());
+        self.function_mut().temp_addresses.release_address(address);
     }
 
     fn add_quad(&mut self, quad: Quadruple) {
         self.quad_list.push(quad);
-        self.safe_remove_temp_address(quad.op_1);
-        self.safe_remove_temp_address(quad.op_2);
+        for operand in [quad.op_1, quad.op_2].into_iter().flatten() {
+            if let QuadOperand::Address(address) = operand {
+                self.safe_remove_temp_address(address);
+            }
+        }
+    }
+
+    /// Adds a quad without releasing the temps it reads, as the quads that
+    /// follow still need them. The caller releases them once they don't
+    fn add_quad_keeping_temps(&mut self, quad: Quadruple) {
+        self.quad_list.push(quad);
     }
 
     fn get_variable<'a>(&mut self, name: &str, node: &AstNode<'a>) -> Results<'a, &Variable> {
@@ -126,10 +157,10 @@ impl QuadrupleManager {
             .or_else(|| self.global_variables().get(name))
         {
             Some(var) => Ok(var),
-            None => Err(RaoulError::new_vec(
-                node,
-                RaoulErrorKind::UndeclaredVar(name.to_string()),
-            )),
+            None => {
+                let tables = [self.function_variables(), self.global_variables()];
+                Err(RaoulError::new_vec(node, undeclared_kind(name, tables)))
+            }
         }
     }
 
@@ -144,41 +175,75 @@ impl QuadrupleManager {
 
     fn parse_args_exprs<'a>(
         &mut self,
-        node: &AstNode<'a>,
         exprs: &[AstNode<'a>],
         args: &[Operand],
     ) -> Results<'a, Vec<Operand>> {
-        if args.len() != exprs.len() {
-            let kind = RaoulErrorKind::UnmatchArgsAmount {
-                expected: args.len(),
-                given: exprs.len(),
-            };
-            return Err(vec![RaoulError::new(node, kind)]);
-        }
-        let addresses = RaoulError::create_partition(exprs.iter().zip(args).map(
-            |(node, (_, arg_type))| -> Results<(usize, Types)> {
-                let (v, v_type) = self.parse_expr(node)?;
-                v_type.assert_cast(*arg_type, node)?;
-                Ok((v, v_type))
-            },
-        ))?;
+        // The function was resolved by its arity, so every argument is given
+        let addresses = RaoulError::create_partition(
+            self.fail_fast,
+            exprs
+                .iter()
+                .zip(args)
+                .map(|(node, (_, arg_type))| -> Results<(usize, Types)> {
+                    let (v, v_type) = self.parse_expr(node)?;
+                    v_type.assert_cast(*arg_type, node)?;
+                    Ok((v, v_type))
+                }),
+        )?;
         Ok(addresses)
     }
 
-    fn add_era_quad(&mut self, name: &str) {
-        let function = self.get_function(name);
+    fn add_era_quad(&mut self, key: &FunctionKey) {
+        let function = self.get_function(key);
         let function_size = function.size();
         let first_quad = function.first_quad;
-        self.add_quad(Quadruple::new_args(
+        self.add_quad(Quadruple::new(
             Operator::Era,
-            function_size,
-            first_quad,
+            Some(Literal(function_size)),
+            Some(Literal(first_quad)),
+            None,
         ));
     }
 
-    fn add_go_sub_quad(&mut self, name: &str) {
-        let first_quad = self.get_function(name).first_quad;
-        self.add_quad(Quadruple::new_arg(Operator::GoSub, first_quad));
+    fn add_go_sub_quad(&mut self, key: &FunctionKey) {
+        let first_quad = self.get_function(key).first_quad;
+        self.add_quad(Quadruple::new(
+            Operator::GoSub,
+            Some(Literal(first_quad)),
+            None,
+            None,
+        ));
+    }
+
+    fn resolve_function<'a>(
+        &self,
+        name: &str,
+        node: &AstNode<'a>,
+        exprs: &[AstNode<'a>],
+    ) -> Results<'a, FunctionKey> {
+        let key = (name.to_owned(), exprs.len());
+        if self.dir_func.functions.contains_key(&key) {
+            return Ok(key);
+        }
+        // Reported against the overload closest to the call
+        let arities = self.dir_func.function_arities(name);
+        let closest = arities
+            .into_iter()
+            .min_by_key(|arity| arity.abs_diff(exprs.len()));
+        let kind = match closest {
+            Some(expected) => RaoulErrorKind::UnmatchArgsAmount {
+                name: name.to_owned(),
+                expected,
+                given: exprs.len(),
+            },
+            None if self.function_variables().contains_key(name)
+                || self.global_variables().contains_key(name) =>
+            {
+                RaoulErrorKind::NotCallable(name.to_owned())
+            }
+            None => RaoulErrorKind::UndeclaredFunction(name.to_owned()),
+        };
+        Err(RaoulError::new_vec(node, kind))
     }
 
     fn parse_func_call<'a>(
@@ -186,18 +251,27 @@ impl QuadrupleManager {
         name: &str,
         node: &AstNode<'a>,
         exprs: &[AstNode<'a>],
-    ) -> Results<'a, ()> {
-        self.add_era_quad(name);
-        let args = &self.get_function(name).args.clone();
-        let addresses = self.parse_args_exprs(node, exprs, args)?;
+    ) -> Results<'a, FunctionKey> {
+        let key = self.resolve_function(name, node, exprs)?;
+        self.add_era_quad(&key);
+        let args = &self.get_function(&key).args.clone();
+        let addresses = self.parse_args_exprs(exprs, args)?;
         addresses
             .into_iter()
             .enumerate()
+            // There's only one dataframe, so it's passed as a handle and its
+            // value doesn't need to be copied
+            .filter(|(_, (_, data_type))| *data_type != Types::Dataframe)
             .for_each(|(i, (address, _))| {
-                self.add_quad(Quadruple::new_un(Operator::Param, address, i));
+                self.add_quad(Quadruple::new(
+                    Operator::Param,
+                    Some(QuadOperand::Address(address)),
+                    None,
+                    Some(Literal(i)),
+                ));
             });
-        self.add_go_sub_quad(name);
-        Ok(())
+        self.add_go_sub_quad(&key);
+        Ok(key)
     }
 
     #[inline]
@@ -206,7 +280,8 @@ impl QuadrupleManager {
         value: VariableValue,
         node: &AstNode<'a>,
     ) -> Results<'a, (usize, Types)> {
-        safe_address(self.memory.add(value), node)
+        let data_type = Types::from(&value);
+        safe_address(self.memory.add(value), data_type, node)
     }
 
     fn add_binary_op_quad<'a>(
@@ -217,11 +292,93 @@ impl QuadrupleManager {
         node: &AstNode<'a>,
     ) -> Results<'a, Operand> {
         let data_type = op_1.1.assert_bin_op(operator, op_2.1, node)?;
+        // The operands are read before the result is written, so the result
+        // can reuse one of their temps, which must stay taken afterwards
+        self.safe_remove_temp_address(op_1.0);
+        self.safe_remove_temp_address(op_2.0);
         let res = self.safe_add_temp(data_type, node)?;
-        self.add_quad(Quadruple::new_com(operator, op_1.0, op_2.0, res));
+        self.add_quad_keeping_temps(Quadruple::new_com(operator, op_1.0, op_2.0, res));
         Ok((res, data_type))
     }
 
+    /// `AND` and `OR` only evaluate their right side when the left one
+    /// doesn't decide the result. Until then the result holds what it would
+    /// be without the right side, `false` for `AND` and `true` for `OR`.
+    fn parse_short_circuit<'a>(
+        &mut self,
+        operator: Operator,
+        lhs: &AstNode<'a>,
+        rhs: &AstNode<'a>,
+        node: &AstNode<'a>,
+    ) -> Results<'a, Operand> {
+        let op_1 = self.parse_expr(lhs)?;
+        op_1.1.assert_bin_op(operator, Types::Bool, node)?;
+        let res = self.safe_add_temp(Types::Bool, node)?;
+        let decided = VariableValue::Bool(operator == Operator::Or);
+        let (decided_address, _) = self.safe_add_cte(decided, node)?;
+        self.add_quad(Quadruple::new_un(
+            Operator::Assignment,
+            decided_address,
+            res,
+        ));
+        // The left side is still needed to compute the result, so the jump
+        // keeps its temp
+        self.jump_list.push(self.quad_list.len());
+        self.add_quad_keeping_temps(Quadruple::new_arg(Operator::GotoF, op_1.0));
+        if operator == Operator::Or {
+            let skip_rhs = self.jump_list.pop().unwrap();
+            self.jump_list.push(self.quad_list.len());
+            self.add_quad(Quadruple::new_empty(Operator::Goto));
+            self.fill_goto_index(skip_rhs);
+        }
+        let op_2 = self.parse_expr(rhs)?;
+        op_1.1.assert_bin_op(operator, op_2.1, node)?;
+        self.add_quad(Quadruple::new_com(operator, op_1.0, op_2.0, res));
+        self.fill_goto();
+        Ok((res, Types::Bool))
+    }
+
+    /// Negative indices count from the end, so the dimension is added to
+    /// them before the bounds check. Constant indices are fixed while
+    /// compiling, the rest while running.
+    fn wrap_index<'a>(
+        &mut self,
+        idx_op: Operand,
+        dim: usize,
+        node: &AstNode<'a>,
+    ) -> Results<'a, Operand> {
+        if Scope::from(idx_op.0) == Scope::Constant {
+            return match *self.memory.get(idx_op.0) {
+                VariableValue::Integer(idx) if idx < 0 => {
+                    self.safe_add_cte(VariableValue::Integer(idx + dim as i64), node)
+                }
+                _ => Ok(idx_op),
+            };
+        }
+        let zero_op = self.safe_add_cte(0.into(), node)?;
+        let dim_op = self.safe_add_cte(dim.into(), node)?;
+        // A temp index can be changed in place, anything else is copied first
+        let index = if idx_op.0.is_temp_address() {
+            idx_op.0
+        } else {
+            let index = self.safe_add_temp(Types::Int, node)?;
+            self.add_quad(Quadruple::new_un(Operator::Assignment, idx_op.0, index));
+            index
+        };
+        let condition = self.safe_add_temp(Types::Bool, node)?;
+        // The index is the result, so none of these quads release it
+        self.add_quad_keeping_temps(Quadruple::new_com(
+            Operator::Lt,
+            index,
+            zero_op.0,
+            condition,
+        ));
+        self.add_goto(Operator::GotoF, Some(condition));
+        self.add_quad_keeping_temps(Quadruple::new_com(Operator::Sum, index, dim_op.0, index));
+        self.fill_goto();
+        Ok((index, Types::Int))
+    }
+
     fn get_array_val_operand<'a>(
         &mut self,
         name: &str,
@@ -247,7 +404,10 @@ impl QuadrupleManager {
         }?;
         let v_address_op = self.safe_add_cte(v.address.into(), node)?;
         let dim_1_op = self.safe_add_cte(dim_1.unwrap().into(), node)?;
-        self.add_quad(Quadruple::new_args(Operator::Ver, idx_1_op.0, dim_1_op.0));
+        let idx_1_op = &self.wrap_index(*idx_1_op, dim_1.unwrap(), node)?;
+        // The indices are still needed to compute the address, so verifying
+        // them must not release their temps
+        self.add_quad_keeping_temps(Quadruple::new_args(Operator::Ver, idx_1_op.0, dim_1_op.0));
         let address: usize = match idx_2_op {
             None => {
                 let pointer = self.pointer_memory.get_pointer();
@@ -261,9 +421,14 @@ impl QuadrupleManager {
             }
             Some(idx_2_op) => {
                 let dim_2_op = self.safe_add_cte(dim_2.unwrap().into(), node)?;
+                let idx_2_op = self.wrap_index(idx_2_op, dim_2.unwrap(), node)?;
                 let mult_op =
                     self.add_binary_op_quad(Operator::Times, *idx_1_op, dim_2_op, node)?;
-                self.add_quad(Quadruple::new_args(Operator::Ver, idx_2_op.0, dim_2_op.0));
+                self.add_quad_keeping_temps(Quadruple::new_args(
+                    Operator::Ver,
+                    idx_2_op.0,
+                    dim_2_op.0,
+                ));
                 let (sum_res, _) =
                     self.add_binary_op_quad(Operator::Sum, v_address_op, mult_op, node)?;
                 let pointer = self.pointer_memory.get_pointer();
@@ -286,9 +451,9 @@ impl QuadrupleManager {
         idx_1: &AstNode<'a>,
         idx_2: Option<BoxedNode<'a>>,
     ) -> Results<'a, Operand> {
-        let idx_1_op = &self.assert_expr_type(idx_1, Types::Int)?;
+        let idx_1_op = &self.assert_expr_exact_type(idx_1, Types::Int)?;
         let idx_2_op = match idx_2 {
-            Some(idx_2) => Some(self.assert_expr_type(&*idx_2, Types::Int)?),
+            Some(idx_2) => Some(self.assert_expr_exact_type(&idx_2, Types::Int)?),
             None => None,
         };
         self.get_array_val_operand(name, node, idx_1_op, idx_2_op)
@@ -306,11 +471,16 @@ impl QuadrupleManager {
         operator: Operator,
         op_1: usize,
         op_2: Option<usize>,
+        data_type: Types,
     ) -> Results<'a, Operand> {
         self.assert_dataframe(name, node)?;
-        let data_type = Types::Float;
         let res = self.safe_add_temp(data_type, node)?;
-        self.add_quad(Quadruple::new(operator, Some(op_1), op_2, Some(res)));
+        self.add_quad(Quadruple::new(
+            operator,
+            Some(QuadOperand::Address(op_1)),
+            op_2.map(QuadOperand::Address),
+            Some(QuadOperand::Address(res)),
+        ));
         Ok((res, data_type))
     }
 
@@ -321,7 +491,7 @@ impl QuadrupleManager {
             | AstNodeKind::Integer(_)
             | AstNodeKind::String(_) => self.safe_add_cte(VariableValue::from(&node.kind), node),
             AstNodeKind::UnaryOperation { operator, operand } => {
-                let (op, op_type) = self.parse_expr(&*operand)?;
+                let (op, op_type) = self.parse_expr(operand)?;
                 let res_type = match operator {
                     Operator::Not => match op_type {
                         Types::Bool | Types::Int => Types::Bool,
@@ -333,6 +503,16 @@ impl QuadrupleManager {
                             return Err(vec![RaoulError::new(node, kind)]);
                         }
                     },
+                    Operator::ToString => match op_type {
+                        Types::Dataframe | Types::Void => {
+                            let kind = RaoulErrorKind::InvalidCast {
+                                from: op_type,
+                                to: Types::String,
+                            };
+                            return Err(vec![RaoulError::new(node, kind)]);
+                        }
+                        _ => Types::String,
+                    },
                     _ => unreachable!(),
                 };
                 let res = self.safe_add_temp(res_type, node)?;
@@ -346,20 +526,69 @@ impl QuadrupleManager {
                     _ => Err(RaoulError::new_vec(node, RaoulErrorKind::UsePrimitive)),
                 }
             }
-            AstNodeKind::Read => {
+            AstNodeKind::Read(prompt) | AstNodeKind::ReadBool(prompt) => {
+                if let Some(prompt) = prompt {
+                    let value = VariableValue::String(prompt.clone());
+                    let (prompt_address, _) = self.safe_add_cte(value, node)?;
+                    self.add_quad(Quadruple::new_arg(Operator::Print, prompt_address));
+                }
+                let (operator, data_type) = match node.kind {
+                    AstNodeKind::Read(_) => (Operator::Read, Types::String),
+                    _ => (Operator::ReadBool, Types::Bool),
+                };
+                let res = self.safe_add_temp(data_type, node)?;
+                self.add_quad(Quadruple::new_res(operator, res));
+                Ok((res, data_type))
+            }
+            AstNodeKind::Argv(index) => {
+                let (index_address, _) = self.assert_expr_exact_type(index, Types::Int)?;
                 let data_type = Types::String;
                 let res = self.safe_add_temp(data_type, node)?;
-                self.add_quad(Quadruple::new_res(Operator::Read, res));
+                self.add_quad(Quadruple::new_un(Operator::Argv, index_address, res));
+                Ok((res, data_type))
+            }
+            AstNodeKind::Random => {
+                let data_type = Types::Float;
+                let res = self.safe_add_temp(data_type, node)?;
+                self.add_quad(Quadruple::new_res(Operator::Random, res));
                 Ok((res, data_type))
             }
+            AstNodeKind::RandomInt { low, high } => {
+                let (low_address, _) = self.assert_expr_exact_type(low, Types::Int)?;
+                let (high_address, _) = self.assert_expr_exact_type(high, Types::Int)?;
+                let data_type = Types::Int;
+                let res = self.safe_add_temp(data_type, node)?;
+                let quad = Quadruple::new_com(Operator::RandomInt, low_address, high_address, res);
+                self.add_quad(quad);
+                Ok((res, data_type))
+            }
+            AstNodeKind::BinaryOperation {
+                operator: operator @ (Operator::And | Operator::Or),
+                lhs,
+                rhs,
+            } => self.parse_short_circuit(*operator, lhs, rhs, node),
             AstNodeKind::BinaryOperation { operator, lhs, rhs } => {
-                let op_1 = self.parse_expr(&*lhs)?;
-                let op_2 = self.parse_expr(&*rhs)?;
+                let op_1 = self.parse_expr(lhs)?;
+                let op_2 = self.parse_expr(rhs)?;
                 self.add_binary_op_quad(*operator, op_1, op_2, node)
             }
             AstNodeKind::FuncCall { name, ref exprs } => {
-                self.parse_func_call(name, node, exprs)?;
-                let (fn_address, return_type) = self.get_variable_name_address(name, node)?;
+                let key = self.parse_func_call(name, node, exprs)?;
+                let function = self.get_function(&key);
+                let (fn_address, return_type) = (function.address, function.return_type);
+                if return_type == Types::Void {
+                    let kind = RaoulErrorKind::VoidFunction(name.to_owned());
+                    return Err(RaoulError::new_vec(node, kind));
+                }
+                let expected = function.returns_amount();
+                if expected > 1 {
+                    let kind = RaoulErrorKind::UnmatchReturnsAmount {
+                        name: name.to_owned(),
+                        expected,
+                        given: 1,
+                    };
+                    return Err(RaoulError::new_vec(node, kind));
+                }
                 let temp_address = self.safe_add_temp(return_type, node)?;
                 self.add_quad(Quadruple::new_un(
                     Operator::Assignment,
@@ -372,7 +601,7 @@ impl QuadrupleManager {
                 ref name,
                 idx_1,
                 idx_2,
-            } => self.arr_val_op_node(name, node, &*idx_1, idx_2.clone()),
+            } => self.arr_val_op_node(name, node, idx_1, idx_2.clone()),
             AstNodeKind::PureDataframeOp { operator, ref name } => {
                 self.assert_dataframe(name, node)?;
                 let data_type = Types::Int;
@@ -385,18 +614,22 @@ impl QuadrupleManager {
                 ref name,
                 column,
             } => {
-                let (column_address, _) = self.assert_expr_type(&*column, Types::String)?;
-                self.dataframe_op(name, node, *operator, column_address, None)
+                let (column_address, _) = self.assert_expr_type(column, Types::String)?;
+                let data_type = match operator {
+                    Operator::NullCount | Operator::ArgMin | Operator::ArgMax => Types::Int,
+                    _ => Types::Float,
+                };
+                self.dataframe_op(name, node, *operator, column_address, None, data_type)
             }
             AstNodeKind::Correlation {
                 ref name,
                 column_1,
                 column_2,
             } => {
-                let (col_1, _) = self.assert_expr_type(&*column_1, Types::String)?;
-                let (col_2, _) = self.assert_expr_type(&*column_2, Types::String)?;
+                let (col_1, _) = self.assert_expr_type(column_1, Types::String)?;
+                let (col_2, _) = self.assert_expr_type(column_2, Types::String)?;
                 let operator = Operator::Corr;
-                self.dataframe_op(name, node, operator, col_1, Some(col_2))
+                self.dataframe_op(name, node, operator, col_1, Some(col_2), Types::Float)
             }
             kind => unreachable!("{kind:?}"),
         }
@@ -412,9 +645,27 @@ impl QuadrupleManager {
         Ok((res_address, res_type))
     }
 
+    /// Like `assert_expr_type`, but without allowing implicit casts. Used where
+    /// the value is consumed as is, like array indices
+    fn assert_expr_exact_type<'a>(
+        &mut self,
+        expr: &AstNode<'a>,
+        to: Types,
+    ) -> Results<'a, Operand> {
+        let (res_address, res_type) = self.parse_expr(expr)?;
+        if res_type != to {
+            let kind = RaoulErrorKind::InvalidCast { from: res_type, to };
+            return Err(RaoulError::new_vec(expr, kind));
+        }
+        Ok((res_address, res_type))
+    }
+
     #[inline]
     fn parse_body<'a>(&mut self, body: &[AstNode<'a>]) -> Results<'a, ()> {
-        RaoulError::create_results(body.iter().map(|node| self.parse_statement(node)))
+        RaoulError::create_results(
+            self.fail_fast,
+            body.iter().map(|node| self.parse_statement(node)),
+        )
     }
 
     fn parse_return_body<'a>(&mut self, body: &[AstNode<'a>]) -> Results<'a, bool> {
@@ -430,14 +681,19 @@ impl QuadrupleManager {
     fn add_goto(&mut self, goto_type: Operator, condition: Option<usize>) {
         debug_assert!(goto_type.is_goto());
         self.jump_list.push(self.quad_list.len());
-        self.add_quad(Quadruple::new(goto_type, condition, None, None));
+        self.add_quad(Quadruple::new(
+            goto_type,
+            condition.map(QuadOperand::Address),
+            None,
+            None,
+        ));
     }
 
     fn fill_goto_index(&mut self, index: usize) {
         let res = self.quad_list.len();
-        let mut quad = self.quad_list.get_mut(index).unwrap();
+        let quad = self.quad_list.get_mut(index).unwrap();
         debug_assert!(quad.operator.is_goto());
-        quad.res = Some(res);
+        quad.res = Some(Literal(res));
     }
 
     fn fill_goto(&mut self) {
@@ -454,34 +710,146 @@ impl QuadrupleManager {
     fn parse_array<'a>(
         &mut self,
         assignee: &AstNode<'a>,
-        exprs: &[AstNode<'a>],
+        value: &AstNode<'a>,
         node: &AstNode<'a>,
     ) -> Results<'a, ()> {
         let name = String::from(assignee);
         let variable = self.get_variable(&name, assignee)?.clone();
         let dim_2 = variable.dimensions.1;
+        // The literal must fill the whole variable, so no value of a previous
+        // assignment is kept
+        let kind = match value.get_dimensions() {
+            Ok(given) if given == variable.dimensions => None,
+            Ok(given) => Some(RaoulErrorKind::MismatchedShape {
+                name: name.clone(),
+                expected: variable.dimensions,
+                given,
+            }),
+            Err((expected, given)) => Some(RaoulErrorKind::InconsistentSize { expected, given }),
+        };
+        if let Some(kind) = kind {
+            return Err(RaoulError::new_vec(value, kind));
+        }
+        let exprs = value.expand_array();
         if dim_2.is_none() {
-            RaoulError::create_results(exprs.iter().enumerate().map(|(i, expr)| -> Results<()> {
-                let idx_1_op = self.safe_add_cte(i.into(), expr)?;
-                let (variable_address, _) =
-                    self.get_array_val_operand(&name, node, &idx_1_op, None)?;
-                self.add_assign_quad(variable_address, expr)
-            }))
+            RaoulError::create_results(
+                self.fail_fast,
+                exprs.iter().enumerate().map(|(i, expr)| -> Results<()> {
+                    let idx_1_op = self.safe_add_cte(i.into(), expr)?;
+                    let (variable_address, _) =
+                        self.get_array_val_operand(&name, node, &idx_1_op, None)?;
+                    self.add_assign_quad(variable_address, expr)
+                }),
+            )
         } else {
-            RaoulError::create_results(exprs.iter().enumerate().map(|(i, exprs)| -> Results<()> {
-                let idx_1_op = self.safe_add_cte(i.into(), exprs)?;
-                RaoulError::create_results(exprs.expand_array().iter().enumerate().map(
-                    |(j, expr)| -> Results<()> {
-                        let idx_2_op = self.safe_add_cte(j.into(), expr)?;
-                        let (variable_address, _) =
-                            self.get_array_val_operand(&name, node, &idx_1_op, Some(idx_2_op))?;
-                        self.add_assign_quad(variable_address, expr)
-                    },
-                ))
-            }))
+            RaoulError::create_results(
+                self.fail_fast,
+                exprs.iter().enumerate().map(|(i, exprs)| -> Results<()> {
+                    let idx_1_op = self.safe_add_cte(i.into(), exprs)?;
+                    RaoulError::create_results(
+                        self.fail_fast,
+                        exprs
+                            .expand_array()
+                            .iter()
+                            .enumerate()
+                            .map(|(j, expr)| -> Results<()> {
+                                let idx_2_op = self.safe_add_cte(j.into(), expr)?;
+                                let (variable_address, _) = self.get_array_val_operand(
+                                    &name,
+                                    node,
+                                    &idx_1_op,
+                                    Some(idx_2_op),
+                                )?;
+                                self.add_assign_quad(variable_address, expr)
+                            }),
+                    )
+                }),
+            )
         }
     }
 
+    fn parse_filled_array<'a>(
+        &mut self,
+        assignee: &AstNode<'a>,
+        value: &AstNode<'a>,
+        size: usize,
+        node: &AstNode<'a>,
+    ) -> Results<'a, ()> {
+        let name = String::from(assignee);
+        let variable = self.get_variable(&name, assignee)?.clone();
+        let dim_1 = match variable.dimensions {
+            (Some(dim_1), None) => Ok(dim_1),
+            (None, _) => Err(RaoulError::new_vec(node, RaoulErrorKind::NotList(name))),
+            _ => Err(RaoulError::new_vec(node, RaoulErrorKind::UsePrimitive)),
+        }?;
+        let (value_address, _) = self.parse_expr(value)?;
+        let zero_op = self.safe_add_cte(0.into(), node)?;
+        let size_op = self.safe_add_cte(size.into(), node)?;
+        let dim_1_op = self.safe_add_cte(dim_1.into(), node)?;
+        let v_address_op = self.safe_add_cte(variable.address.into(), node)?;
+        // Instead of unrolling one assignment per element, a counter walks
+        // the array. The counter and the value are used on every iteration,
+        // so their temps are only released once the loop is emitted
+        let counter = self.safe_add_temp(Types::Int, node)?;
+        self.add_quad(Quadruple::new_un(Operator::Assignment, zero_op.0, counter));
+        let loop_start = self.quad_list.len();
+        let condition = self.safe_add_temp(Types::Bool, node)?;
+        self.add_quad_keeping_temps(Quadruple::new_com(
+            Operator::Lt,
+            counter,
+            size_op.0,
+            condition,
+        ));
+        self.add_goto(Operator::GotoF, Some(condition));
+        self.add_quad_keeping_temps(Quadruple::new_args(Operator::Ver, counter, dim_1_op.0));
+        let pointer = self.pointer_memory.get_pointer();
+        self.add_quad_keeping_temps(Quadruple::new_com(
+            Operator::Sum,
+            v_address_op.0,
+            counter,
+            pointer,
+        ));
+        self.add_quad_keeping_temps(Quadruple::new_un(
+            Operator::Assignment,
+            value_address,
+            pointer,
+        ));
+        self.add_quad(Quadruple::new_res(Operator::Inc, counter));
+        self.add_quad(Quadruple::new(
+            Operator::Goto,
+            None,
+            None,
+            Some(Literal(loop_start)),
+        ));
+        self.fill_goto();
+        self.safe_remove_temp_address(counter);
+        self.safe_remove_temp_address(value_address);
+        Ok(())
+    }
+
+    /// Assigns each field of the record to its own variable
+    fn parse_record<'a>(
+        &mut self,
+        assignee: &AstNode<'a>,
+        fields: &[(String, AstNode<'a>)],
+    ) -> Results<'a, ()> {
+        let record = String::from(assignee);
+        RaoulError::create_results(
+            self.fail_fast,
+            fields.iter().map(|(field, value)| {
+                let name = format!("{record}.{field}");
+                let (address, data_type) = self.get_variable_name_address(&name, value)?;
+                let (value_address, _) = self.assert_expr_type(value, data_type)?;
+                self.add_quad(Quadruple::new_un(
+                    Operator::Assignment,
+                    value_address,
+                    address,
+                ));
+                Ok(())
+            }),
+        )
+    }
+
     fn parse_assignment<'a>(
         &mut self,
         assignee: &AstNode<'a>,
@@ -491,12 +859,51 @@ impl QuadrupleManager {
     ) -> Results<'a, ()> {
         match &value.kind {
             AstNodeKind::ArrayDeclaration { .. } => Ok(()),
-            AstNodeKind::Array(exprs) => self.parse_array(assignee, exprs, node),
+            AstNodeKind::Record { fields, .. } => self.parse_record(assignee, fields),
+            AstNodeKind::Array(_) => self.parse_array(assignee, value, node),
+            AstNodeKind::FilledArray { value, size } => {
+                self.parse_filled_array(assignee, value, *size, node)
+            }
             AstNodeKind::ReadCSV(file_node) => {
-                let (file_address, _) = self.assert_expr_type(&*file_node, Types::String)?;
+                let (file_address, _) = self.assert_expr_type(file_node, Types::String)?;
                 self.add_quad(Quadruple::new_arg(Operator::ReadCSV, file_address));
                 Ok(())
             }
+            AstNodeKind::Split {
+                text,
+                separator,
+                size,
+            } => {
+                let (text_address, _) = self.assert_expr_type(text, Types::String)?;
+                let (separator_address, _) = self.assert_expr_type(separator, Types::String)?;
+                let assignee_name = String::from(assignee);
+                let address = self.get_variable_address(global, &assignee_name);
+                // The parts are kept by the VM until they're written to the array
+                self.add_quad(Quadruple::new_args(
+                    Operator::Split,
+                    text_address,
+                    separator_address,
+                ));
+                self.add_quad(Quadruple::new(
+                    Operator::SplitParts,
+                    Some(Literal(*size)),
+                    None,
+                    Some(QuadOperand::Address(address)),
+                ));
+                Ok(())
+            }
+            AstNodeKind::ColumnsOf { name, size } => {
+                self.assert_dataframe(name, value)?;
+                let assignee_name = String::from(assignee);
+                let address = self.get_variable_address(global, &assignee_name);
+                self.add_quad(Quadruple::new(
+                    Operator::ColumnsOf,
+                    Some(Literal(*size)),
+                    None,
+                    Some(QuadOperand::Address(address)),
+                ));
+                Ok(())
+            }
             _ => {
                 let variable_address = if let AstNodeKind::ArrayVal {
                     ref name,
@@ -504,7 +911,7 @@ impl QuadrupleManager {
                     idx_2,
                 } = &assignee.kind
                 {
-                    let op = self.arr_val_op_node(name, node, &*idx_1, idx_2.clone())?;
+                    let op = self.arr_val_op_node(name, node, idx_1, idx_2.clone())?;
                     op.0
                 } else {
                     let name: String = assignee.into();
@@ -515,6 +922,26 @@ impl QuadrupleManager {
         }
     }
 
+    /// Rejects loops whose bounds are constants that make it never run.
+    /// Bounds that aren't constants are left for the runtime.
+    fn assert_loop_runs<'a>(
+        assignment: &AstNode<'a>,
+        expr: &AstNode<'a>,
+        node: &AstNode<'a>,
+    ) -> Results<'a, ()> {
+        if let (AstNodeKind::Assignment { value, .. }, AstNodeKind::BinaryOperation { rhs, .. }) =
+            (&assignment.kind, &expr.kind)
+        {
+            if let (Some(start), Some(stop)) = (value.const_int(), rhs.const_int()) {
+                if start > stop {
+                    let kind = RaoulErrorKind::DegenerateLoop { start, stop };
+                    return Err(RaoulError::new_vec(node, kind));
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn parse_for<'a>(
         &mut self,
         assignment: &AstNode<'a>,
@@ -522,6 +949,7 @@ impl QuadrupleManager {
         statements: &[AstNode<'a>],
         node: &AstNode<'a>,
     ) -> Results<'a, ()> {
+        Self::assert_loop_runs(assignment, expr, node)?;
         let name = String::from(assignment);
         self.parse_statement(assignment)?;
         self.jump_list.push(self.quad_list.len());
@@ -533,24 +961,214 @@ impl QuadrupleManager {
         self.add_quad(Quadruple::new_res(Operator::Inc, var_address));
         let index = self.jump_list.pop().unwrap();
         let goto_res = self.jump_list.pop().unwrap();
-        self.add_quad(Quadruple::new_res(Operator::Goto, goto_res));
+        self.add_quad(Quadruple::new(
+            Operator::Goto,
+            None,
+            None,
+            Some(Literal(goto_res)),
+        ));
+        self.fill_goto_index(index);
+        Ok(())
+    }
+
+    fn parse_for_each<'a>(
+        &mut self,
+        name: &str,
+        array: &str,
+        statements: &[AstNode<'a>],
+        node: &AstNode<'a>,
+    ) -> Results<'a, ()> {
+        let size = match self.get_variable(array, node)?.dimensions {
+            (Some(size), None) => size,
+            (Some(rows), Some(columns)) => {
+                let kind = RaoulErrorKind::MatrixNotList {
+                    name: array.to_owned(),
+                    rows,
+                    columns,
+                };
+                return Err(RaoulError::new_vec(node, kind));
+            }
+            (None, _) => {
+                let kind = RaoulErrorKind::NotList(array.to_owned());
+                return Err(RaoulError::new_vec(node, kind));
+            }
+        };
+        let (element_address, _) = self.get_variable_name_address(name, node)?;
+        let zero_op = self.safe_add_cte(0.into(), node)?;
+        let size_op = self.safe_add_cte(size.into(), node)?;
+        // The counter is used on every iteration, so its temp is only
+        // released once the loop is emitted
+        let counter = self.safe_add_temp(Types::Int, node)?;
+        self.add_quad(Quadruple::new_un(Operator::Assignment, zero_op.0, counter));
+        self.jump_list.push(self.quad_list.len());
+        let condition = self.safe_add_temp(Types::Bool, node)?;
+        self.add_quad_keeping_temps(Quadruple::new_com(
+            Operator::Lt,
+            counter,
+            size_op.0,
+            condition,
+        ));
+        self.add_goto(Operator::GotoF, Some(condition));
+        // Reading the element releases the index, so it reads a copy
+        let index = self.safe_add_temp(Types::Int, node)?;
+        self.add_quad_keeping_temps(Quadruple::new_un(Operator::Assignment, counter, index));
+        let (pointer, _) = self.get_array_val_operand(array, node, &(index, Types::Int), None)?;
+        self.add_quad(Quadruple::new_un(
+            Operator::Assignment,
+            pointer,
+            element_address,
+        ));
+        self.parse_return_body(statements)?;
+        self.add_quad(Quadruple::new_res(Operator::Inc, counter));
+        let index = self.jump_list.pop().unwrap();
+        let goto_res = self.jump_list.pop().unwrap();
+        self.add_quad(Quadruple::new(
+            Operator::Goto,
+            None,
+            None,
+            Some(Literal(goto_res)),
+        ));
+        self.fill_goto_index(index);
+        self.safe_remove_temp_address(counter);
+        Ok(())
+    }
+
+    fn parse_repeat<'a>(
+        &mut self,
+        count: &AstNode<'a>,
+        statements: &[AstNode<'a>],
+        node: &AstNode<'a>,
+    ) -> Results<'a, ()> {
+        let constant = count.const_int();
+        if let Some(count) = constant.filter(|count| *count < 0) {
+            return Err(RaoulError::new_vec(
+                node,
+                RaoulErrorKind::NegativeRepeat(count),
+            ));
+        }
+        let (count_address, _) = self.assert_expr_exact_type(count, Types::Int)?;
+        // The count is copied so changing it inside the body doesn't change
+        // how many times the loop runs. Like the hidden counter, its temp is
+        // only released once the loop is emitted
+        let total = self.safe_add_temp(Types::Int, node)?;
+        self.add_quad(Quadruple::new_un(
+            Operator::Assignment,
+            count_address,
+            total,
+        ));
+        if constant.is_none() {
+            self.add_quad_keeping_temps(Quadruple::new_arg(Operator::VerCount, total));
+        }
+        let zero_op = self.safe_add_cte(0.into(), node)?;
+        let counter = self.safe_add_temp(Types::Int, node)?;
+        self.add_quad(Quadruple::new_un(Operator::Assignment, zero_op.0, counter));
+        self.jump_list.push(self.quad_list.len());
+        let condition = self.safe_add_temp(Types::Bool, node)?;
+        self.add_quad_keeping_temps(Quadruple::new_com(Operator::Lt, counter, total, condition));
+        self.add_goto(Operator::GotoF, Some(condition));
+        self.parse_return_body(statements)?;
+        self.add_quad(Quadruple::new_res(Operator::Inc, counter));
+        let index = self.jump_list.pop().unwrap();
+        let goto_res = self.jump_list.pop().unwrap();
+        self.add_quad(Quadruple::new(
+            Operator::Goto,
+            None,
+            None,
+            Some(Literal(goto_res)),
+        ));
         self.fill_goto_index(index);
+        self.safe_remove_temp_address(counter);
+        self.safe_remove_temp_address(total);
         Ok(())
     }
 
+    fn parse_match<'a>(
+        &mut self,
+        expr: &AstNode<'a>,
+        arms: &[(AstNode<'a>, Vec<AstNode<'a>>)],
+        default: &Option<Vec<AstNode<'a>>>,
+        node: &AstNode<'a>,
+    ) -> Results<'a, ()> {
+        let (value_address, _) = self.assert_expr_exact_type(expr, Types::Int)?;
+        let mut arms_miss_return = false;
+        for (arm_value, statements) in arms {
+            let (arm_address, _) = self.parse_expr(arm_value)?;
+            let res = self.safe_add_temp(Types::Bool, node)?;
+            // The matched value is compared once per arm, so it can't be
+            // released until every comparison has been emitted
+            self.add_quad_keeping_temps(Quadruple::new_com(
+                Operator::Eq,
+                value_address,
+                arm_address,
+                res,
+            ));
+            self.add_goto(Operator::GotoF, Some(res));
+            arms_miss_return |= self.parse_return_body(statements)?;
+            let index = self.jump_list.pop().unwrap();
+            self.add_goto(Operator::Goto, None);
+            self.fill_goto_index(index);
+        }
+        if let Some(statements) = default {
+            self.parse_body(statements)?;
+            if arms_miss_return && !self.missing_return {
+                self.missing_return = true;
+            }
+        }
+        arms.iter().for_each(|_| self.fill_goto());
+        self.safe_remove_temp_address(value_address);
+        Ok(())
+    }
+
+    /// Remembers the source line where the node starts, unless it's the
+    /// line that was marked last
+    fn mark_source_line(&mut self, node: &AstNode) {
+        let start = node.span.start_pos();
+        let line = start.line_col().0;
+        let last_line = self.source_lines.values().flatten().last();
+        if last_line.map(|(last, _)| *last) == Some(line) {
+            return;
+        }
+        let text = start.line_of().trim().to_owned();
+        self.source_lines
+            .entry(self.quad_list.len())
+            .or_default()
+            .push((line, text));
+    }
+
     fn parse_statement<'a>(&mut self, node: &AstNode<'a>) -> Results<'a, ()> {
+        // The else keyword shares its line with the end of the if block
+        if !matches!(node.kind, AstNodeKind::ElseBlock(_)) {
+            self.mark_source_line(node);
+        }
         match &node.kind {
             AstNodeKind::Assignment {
                 assignee,
                 global,
                 value,
-            } => self.parse_assignment(&*assignee, *global, &*value, node),
-            AstNodeKind::Write(exprs) => {
-                RaoulError::create_results(exprs.iter().map(|expr| -> Results<()> {
-                    let (address, _) = self.parse_expr(expr)?;
-                    self.add_quad(Quadruple::new_arg(Operator::Print, address));
-                    Ok(())
-                }))?;
+            } => self.parse_assignment(assignee, *global, value, node),
+            AstNodeKind::Write { exprs, separator } => {
+                let separator = separator.clone().unwrap_or_else(|| " ".to_owned());
+                let last = exprs.len().saturating_sub(1);
+                RaoulError::create_results(
+                    self.fail_fast,
+                    exprs.iter().enumerate().map(|(i, expr)| -> Results<()> {
+                        let (address, _) = self.parse_expr(expr)?;
+                        // The last value is followed by the new line instead
+                        let separator_address = if i == last {
+                            None
+                        } else {
+                            let value = VariableValue::String(separator.clone());
+                            Some(self.safe_add_cte(value, expr)?.0)
+                        };
+                        self.add_quad(Quadruple::new(
+                            Operator::Print,
+                            Some(QuadOperand::Address(address)),
+                            separator_address.map(QuadOperand::Address),
+                            None,
+                        ));
+                        Ok(())
+                    }),
+                )?;
                 self.add_quad(Quadruple::new_empty(Operator::PrintNl));
                 Ok(())
             }
@@ -559,14 +1177,14 @@ impl QuadrupleManager {
                 statements,
                 else_block,
             } => {
-                let (res_address, _) = self.assert_expr_type(&*expr, Types::Bool)?;
+                let (res_address, _) = self.assert_expr_type(expr, Types::Bool)?;
                 self.add_goto(Operator::GotoF, Some(res_address));
                 let if_misses_return = self.parse_return_body(statements)?;
                 if let Some(node) = else_block {
                     let index = self.jump_list.pop().unwrap();
                     self.add_goto(Operator::Goto, None);
                     self.fill_goto_index(index);
-                    self.parse_statement(&*node)?;
+                    self.parse_statement(node)?;
                     self.fill_goto();
                     if if_misses_return && !self.missing_return {
                         self.missing_return = true;
@@ -579,34 +1197,176 @@ impl QuadrupleManager {
             AstNodeKind::ElseBlock(statements) => self.parse_body(statements),
             AstNodeKind::While { expr, statements } => {
                 self.jump_list.push(self.quad_list.len());
-                let (res_address, _) = self.assert_expr_type(&*expr, Types::Bool)?;
+                let (res_address, _) = self.assert_expr_type(expr, Types::Bool)?;
                 self.add_goto(Operator::GotoF, Some(res_address));
                 self.parse_return_body(statements)?;
                 let index = self.jump_list.pop().unwrap();
                 let goto_res = self.jump_list.pop().unwrap();
-                self.add_quad(Quadruple::new_res(Operator::Goto, goto_res));
+                self.add_quad(Quadruple::new(
+                    Operator::Goto,
+                    None,
+                    None,
+                    Some(Literal(goto_res)),
+                ));
                 self.fill_goto_index(index);
                 Ok(())
             }
+            AstNodeKind::ForEach {
+                name,
+                array,
+                statements,
+            } => self.parse_for_each(name, array, statements, node),
+            AstNodeKind::Repeat { count, statements } => self.parse_repeat(count, statements, node),
+            AstNodeKind::Try { body, handler } => {
+                self.add_goto(Operator::Try, None);
+                let body_misses_return = self.parse_return_body(body)?;
+                self.add_quad(Quadruple::new_empty(Operator::EndTry));
+                let index = self.jump_list.pop().unwrap();
+                self.add_goto(Operator::Goto, None);
+                self.fill_goto_index(index);
+                self.parse_body(handler)?;
+                self.fill_goto();
+                if body_misses_return && !self.missing_return {
+                    self.missing_return = true;
+                }
+                Ok(())
+            }
             AstNodeKind::For {
                 assignment,
                 expr,
                 statements,
-            } => self.parse_for(&*assignment, &*expr, statements, node),
-            AstNodeKind::Return(expr) => {
+            } => self.parse_for(assignment, expr, statements, node),
+            AstNodeKind::Match {
+                expr,
+                arms,
+                default,
+            } => self.parse_match(expr, arms, default, node),
+            AstNodeKind::Return(None) => {
                 let return_type = self.function().return_type;
-                let (expr_address, _) = self.assert_expr_type(&*expr, return_type)?;
+                if return_type != Types::Void {
+                    let kind = RaoulErrorKind::InvalidCast {
+                        from: Types::Void,
+                        to: return_type,
+                    };
+                    return Err(RaoulError::new_vec(node, kind));
+                }
+                // Returning from main finishes the program
+                let operator = match self.function_key.0.as_str() {
+                    "main" => Operator::End,
+                    _ => Operator::Return,
+                };
+                self.add_quad(Quadruple::new_empty(operator));
+                Ok(())
+            }
+            AstNodeKind::Return(Some(expr)) => {
+                let function = self.function();
+                let return_type = function.return_type;
+                let extra_returns = function.extra_returns.clone();
+                let expected = function.returns_amount();
+                let values: Vec<_> = match &expr.kind {
+                    AstNodeKind::Tuple(values) => values.iter().collect(),
+                    _ => vec![expr.as_ref()],
+                };
+                // A value returned from a void function is reported as a cast
+                if values.len() != expected && (expected != 0 || values.len() > 1) {
+                    let kind = RaoulErrorKind::UnmatchReturnsAmount {
+                        name: self.function_key.0.clone(),
+                        expected,
+                        given: values.len(),
+                    };
+                    return Err(RaoulError::new_vec(node, kind));
+                }
+                let (expr_address, _) = self.assert_expr_type(values[0], return_type)?;
+                // Every value is computed before writing any slot, as they
+                // could call this same function
+                let mut extra_addresses = Vec::new();
+                for (value, (_, data_type)) in values[1..].iter().zip(&extra_returns) {
+                    extra_addresses.push(self.assert_expr_type(value, *data_type)?.0);
+                }
+                for (address, (slot, _)) in extra_addresses.into_iter().zip(extra_returns) {
+                    self.add_quad(Quadruple::new_un(Operator::Assignment, address, slot));
+                }
                 self.missing_return = false;
                 self.add_quad(Quadruple::new_arg(Operator::Return, expr_address));
                 Ok(())
             }
-            AstNodeKind::FuncCall { ref name, exprs } => {
-                if self.dir_func.functions.get(name).is_some() {
-                    self.parse_func_call(name, node, exprs)
-                } else {
-                    let kind = RaoulErrorKind::UndeclaredFunction2(name.to_string());
-                    Err(RaoulError::new_vec(node, kind))
+            AstNodeKind::MultiAssignment { assignees, call } => {
+                let (name, exprs) = match &call.kind {
+                    AstNodeKind::FuncCall { name, exprs } => (name, exprs),
+                    kind => unreachable!("{kind:?}"),
+                };
+                let key = self.parse_func_call(name, call, exprs)?;
+                // The declarations already checked that every value is assigned,
+                // so each assignee reads the slot of its value
+                RaoulError::create_results(
+                    self.fail_fast,
+                    assignees.iter().enumerate().map(|(i, assignee)| {
+                        let slot = return_slot_name(&key.0, key.1, i);
+                        let value = AstNode::new(AstNodeKind::Id(slot), &call.span);
+                        self.parse_assignment(assignee, false, &value, node)
+                    }),
+                )
+            }
+            AstNodeKind::Error(message) => {
+                let value = VariableValue::String(message.clone());
+                let (message_address, _) = self.safe_add_cte(value, node)?;
+                // The program stops here, so this branch doesn't need a return
+                self.missing_return = false;
+                self.add_quad(Quadruple::new_arg(Operator::Abort, message_address));
+                Ok(())
+            }
+            AstNodeKind::Increment { operator, assignee } => {
+                let (address, data_type) = self.parse_expr(assignee)?;
+                if !matches!(data_type, Types::Int | Types::Float) {
+                    let kind = RaoulErrorKind::InvalidCast {
+                        from: data_type,
+                        to: Types::Float,
+                    };
+                    return Err(RaoulError::new_vec(node, kind));
                 }
+                self.add_quad(Quadruple::new_res(*operator, address));
+                Ok(())
+            }
+            AstNodeKind::Append { name, value } => {
+                let variable = self.get_variable(name, node)?.clone();
+                if variable.dimensions.0.is_some() {
+                    return Err(RaoulError::new_vec(node, RaoulErrorKind::UsePrimitive));
+                }
+                if variable.data_type != Types::String {
+                    let kind = RaoulErrorKind::InvalidCast {
+                        from: variable.data_type,
+                        to: Types::String,
+                    };
+                    return Err(RaoulError::new_vec(node, kind));
+                }
+                let (value_address, _) = self.assert_expr_exact_type(value, Types::String)?;
+                self.add_quad(Quadruple::new_un(
+                    Operator::Append,
+                    value_address,
+                    variable.address,
+                ));
+                Ok(())
+            }
+            AstNodeKind::Assert { expr, message } => {
+                let (res_address, _) = self.assert_expr_type(expr, Types::Bool)?;
+                let message_address = match message {
+                    Some(message) => {
+                        let value = VariableValue::String(message.clone());
+                        Some(self.safe_add_cte(value, node)?.0)
+                    }
+                    None => None,
+                };
+                self.add_quad(Quadruple::new(
+                    Operator::Assert,
+                    Some(QuadOperand::Address(res_address)),
+                    message_address.map(QuadOperand::Address),
+                    None,
+                ));
+                Ok(())
+            }
+            AstNodeKind::FuncCall { ref name, exprs } => {
+                self.parse_func_call(name, node, exprs)?;
+                Ok(())
             }
             AstNodeKind::Plot {
                 name,
@@ -614,18 +1374,60 @@ impl QuadrupleManager {
                 column_2,
             } => {
                 self.assert_dataframe(name, node)?;
-                let (col_1, _) = self.assert_expr_type(&*column_1, Types::String)?;
-                let (col_2, _) = self.assert_expr_type(&*column_2, Types::String)?;
+                let (col_1, _) = self.assert_expr_type(column_1, Types::String)?;
+                let (col_2, _) = self.assert_expr_type(column_2, Types::String)?;
                 self.add_quad(Quadruple::new_args(Operator::Plot, col_1, col_2));
                 Ok(())
             }
-            AstNodeKind::Histogram { bins, column, name } => {
+            AstNodeKind::Histogram {
+                bins,
+                column,
+                name,
+                density,
+            } => {
                 self.assert_dataframe(name, node)?;
-                let (col, _) = self.assert_expr_type(&*column, Types::String)?;
-                let (bins, _) = self.assert_expr_type(&*bins, Types::Int)?;
+                let (col, _) = self.assert_expr_type(column, Types::String)?;
+                let (bins, _) = self.assert_expr_type(bins, Types::Int)?;
+                // There's no operand left for the density, so it's kept by the
+                // VM until the histogram is drawn
+                if let Some(density) = density {
+                    let (density, _) = self.assert_expr_type(density, Types::Bool)?;
+                    self.add_quad(Quadruple::new_arg(Operator::Density, density));
+                }
                 self.add_quad(Quadruple::new_args(Operator::Histogram, col, bins));
                 Ok(())
             }
+            AstNodeKind::CastColumn {
+                name,
+                column,
+                data_type,
+            } => {
+                self.assert_dataframe(name, node)?;
+                let (col, _) = self.assert_expr_type(column, Types::String)?;
+                let value = VariableValue::String(data_type.to_string());
+                let (type_address, _) = self.safe_add_cte(value, node)?;
+                self.add_quad(Quadruple::new_args(Operator::CastColumn, col, type_address));
+                Ok(())
+            }
+            AstNodeKind::Describe(ref name) => {
+                self.assert_dataframe(name, node)?;
+                self.add_quad(Quadruple::new_empty(Operator::Describe));
+                Ok(())
+            }
+            AstNodeKind::Show { ref name, rows } => {
+                self.assert_dataframe(name, node)?;
+                let rows = match rows {
+                    Some(rows) => Some(self.assert_expr_exact_type(rows, Types::Int)?.0),
+                    None => None,
+                };
+                self.add_quad(Quadruple::new(
+                    Operator::Show,
+                    rows.map(QuadOperand::Address),
+                    None,
+                    None,
+                ));
+                Ok(())
+            }
             kind => unreachable!("{kind:?}"),
         }
     }
@@ -638,15 +1440,20 @@ impl QuadrupleManager {
     pub fn parse<'a>(&mut self, node: &AstNode<'a>) -> Results<'a, ()> {
         match &node.kind {
             AstNodeKind::Main {
-                body,
+                body: Some(body),
                 functions,
                 assignments,
+                ..
             } => {
                 self.add_goto(Operator::Goto, None);
-                RaoulError::create_results(functions.iter().map(|node| self.parse(node)))?;
+                RaoulError::create_results(
+                    self.fail_fast,
+                    functions.iter().map(|node| self.parse(node)),
+                )?;
                 self.fill_goto();
-                self.function_name = "main".to_owned();
+                self.function_key = ("main".to_owned(), 0);
                 RaoulError::create_results(
+                    self.fail_fast,
                     assignments.iter().map(|node| self.parse_statement(node)),
                 )?;
                 self.parse_body(body)?;
@@ -657,9 +1464,12 @@ impl QuadrupleManager {
                 name,
                 body,
                 return_type,
+                arguments,
                 ..
             } => {
-                self.function_name = name.clone();
+                self.function_key = (name.clone(), arguments.len());
+                self.mark_source_line(node);
+                let pending_jumps = self.jump_list.len();
                 let first_quad = self.quad_list.len();
                 self.update_quad(first_quad);
                 if *return_type != Types::Void {
@@ -667,9 +1477,17 @@ impl QuadrupleManager {
                 }
                 self.parse_body(body)?;
                 if self.missing_return {
-                    let kind = RaoulErrorKind::MissingReturn(self.function_name.clone());
-                    return Err(vec![RaoulError::new(node, kind)]);
+                    let kind = RaoulErrorKind::MissingReturn {
+                        name: self.function_key.0.clone(),
+                        return_type: *return_type,
+                    };
+                    // The function falls through after its last statement
+                    let fall_through = body.last().unwrap_or(node);
+                    return Err(vec![RaoulError::new(fall_through, kind)]);
                 }
+                // Returns don't go through the jump list, so every jump the
+                // body added must have been filled by the statement adding it
+                debug_assert_eq!(self.jump_list.len(), pending_jumps);
                 self.add_quad(Quadruple::new_empty(Operator::EndProc));
                 Ok(())
             }
@@ -678,12 +1496,26 @@ impl QuadrupleManager {
     }
 }
 
+impl QuadrupleManager {
+    /// The quads listing, with the source line each statement came from as a
+    /// comment before its first quad
+    pub fn annotated(&self) -> String {
+        let mut listing = String::new();
+        for (i, quad) in self.quads().iter().enumerate() {
+            for (line, text) in self.source_lines.get(&i).into_iter().flatten() {
+                listing += &format!("     // {line}: {text}\n");
+            }
+            listing += &format!("{:<4} - {:?}\n", i, quad);
+        }
+        listing
+    }
+}
+
 impl fmt::Display for QuadrupleManager {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let value: String = self
-            .quad_list
-            .clone()
-            .into_iter()
+            .quads()
+            .iter()
             .enumerate()
             .map(|(i, quad)| format!("{:<4} - {:?}\n", i, quad))
             .collect();
@@ -1,6 +1,15 @@
-use super::{parse, parse_ast, AstNode, QuadrupleManager, VM};
+use super::{eval, parse, parse_ast, vm::VM, AstNode, CompileOptions, QuadrupleManager, VERSION};
+use crate::{
+    address::GenericAddressManager,
+    ast::printer::format_program,
+    dir_func::DirFunc,
+    enums::Operator,
+    quadruple::bytecode::Bytecode,
+    quadruple::{operand::QuadOperand, quadruple::Quadruple},
+};
+use std::fs::read_dir;
 
-fn get_ast(program: &str) -> AstNode {
+fn get_ast(program: &str) -> AstNode<'_> {
     let ast_response = parse(program, false);
     assert!(ast_response.is_ok());
     let ast = ast_response.unwrap();
@@ -8,20 +17,36 @@ fn get_ast(program: &str) -> AstNode {
     ast
 }
 
+/// Compiles a program that must be syntactically valid, with its errors as
+/// they're reported
+fn compile_with(program: &str, options: CompileOptions) -> Result<QuadrupleManager, Vec<String>> {
+    let ast = parse(program, false).unwrap();
+    parse_ast(&ast, options).map_err(|errors| errors.iter().map(ToString::to_string).collect())
+}
+
+pub(crate) fn compile(program: &str) -> Result<QuadrupleManager, Vec<String>> {
+    compile_with(program, CompileOptions::default())
+}
+
 fn parse_ast_has_error(filename: &str) {
     println!("Testing {:?}", filename);
     let program = std::fs::read_to_string(filename).expect(filename);
     let ast = get_ast(&program);
-    let res = parse_ast(&ast, false, false);
+    let res = parse_ast(&ast, CompileOptions::default());
     assert!(res.is_err());
-    insta::assert_debug_snapshot!(res.unwrap_err());
+    let errors: String = res
+        .unwrap_err()
+        .iter()
+        .map(|error| format!("{error}\n"))
+        .collect();
+    insta::assert_display_snapshot!(errors);
 }
 
 fn parse_ast_is_ok(filename: &str) -> QuadrupleManager {
     println!("Testing {:?}", filename);
     let program = std::fs::read_to_string(filename).expect(filename);
     let ast = get_ast(&program);
-    let res = parse_ast(&ast, false, false);
+    let res = parse_ast(&ast, CompileOptions::default());
     assert!(res.is_ok());
     let quad_manager = res.unwrap();
     insta::assert_display_snapshot!(quad_manager);
@@ -30,7 +55,7 @@ fn parse_ast_is_ok(filename: &str) -> QuadrupleManager {
 
 fn run_vm_is_error(filename: &str) {
     let quad_manager = parse_ast_is_ok(filename);
-    let mut vm = VM::new(&quad_manager, false);
+    let mut vm = VM::new(&quad_manager, false, false);
     let res = vm.run();
     assert!(res.is_err());
     insta::assert_display_snapshot!(res.unwrap_err());
@@ -39,7 +64,7 @@ fn run_vm_is_error(filename: &str) {
 
 fn run_vm_is_ok(filename: &str) {
     let quad_manager = parse_ast_is_ok(filename);
-    let mut vm = VM::new(&quad_manager, false);
+    let mut vm = VM::new(&quad_manager, false, false);
     let res = vm.run();
     assert!(res.is_ok());
     insta::assert_debug_snapshot!(vm.messages);
@@ -67,5 +92,855 @@ fn vm_running_invalid_files() {
 
 #[test]
 fn valid_files() {
-    expect_paths("examples/valid/*", run_vm_is_ok);
+    expect_paths("examples/valid/*.ra", run_vm_is_ok);
+}
+
+#[test]
+fn golden_outputs() {
+    for path in read_dir("src/examples/valid").unwrap() {
+        let path = path.expect("File must exist").path();
+        let expected = match std::fs::read_to_string(path.with_extension("out")) {
+            Ok(expected) if path.extension() == Some("ra".as_ref()) => expected,
+            _ => continue,
+        };
+        let program = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(eval(&program).unwrap().concat(), expected, "{path:?}");
+    }
+}
+
+#[test]
+fn bytecode_round_trips() {
+    for path in read_dir("src/examples/valid").unwrap() {
+        let path = path.expect("File must exist").path();
+        let expected = match std::fs::read_to_string(path.with_extension("out")) {
+            Ok(expected) if path.extension() == Some("ra".as_ref()) => expected,
+            _ => continue,
+        };
+        let program = std::fs::read_to_string(&path).unwrap();
+        let quad_manager = compile(&program).unwrap();
+        let bytecode = Bytecode::from(&quad_manager);
+        let loaded = Bytecode::from_json(&bytecode.to_json()).unwrap();
+        assert_eq!(loaded, bytecode, "{path:?}");
+        let mut vm = VM::from_bytecode(loaded, false, false);
+        assert_eq!(vm.run_collect().unwrap(), expected, "{path:?}");
+    }
+}
+
+#[test]
+fn bytecode_from_another_version_is_rejected() {
+    let program = "func main(): void { print(1); }";
+    let quad_manager = compile(program).unwrap();
+    let mut bytecode = Bytecode::from(&quad_manager);
+    bytecode.version = "0.0.1".to_owned();
+    let error = Bytecode::from_json(&bytecode.to_json()).unwrap_err();
+    assert_eq!(
+        error,
+        format!("Bytecode compiled by raoul 0.0.1, it can't be run by raoul {VERSION}")
+    );
+    assert!(Bytecode::from_json("{}")
+        .unwrap_err()
+        .starts_with("Invalid bytecode"));
+}
+
+#[test]
+fn symbols_are_listed_by_address() {
+    let program = "
+        scores = [1.5, 2.5, 3.5];
+        func double(n: int): int { return n * 2; }
+        func main(): void {
+            total = 0;
+            grid = [[1, 2, 3], [4, 5, 6]];
+            print(double(total));
+        }
+    ";
+    let ast = parse(program, false).unwrap();
+    let mut dir_func = DirFunc::new();
+    assert!(dir_func.build_dir_func(&ast, false).is_ok());
+    let expected = "global
+  G0      double/1 int
+  G2500   scores   float[3]
+
+func double(int): int
+  L0      n int
+
+func main(): void
+  L0      total int
+  L1      grid  int[2][3]
+";
+    assert_eq!(dir_func.symbols(), expected);
+}
+
+#[test]
+fn formatting_round_trips() {
+    for path in read_dir("src/examples/valid").unwrap() {
+        let path = path.expect("File must exist").path();
+        if path.extension() != Some("ra".as_ref()) {
+            continue;
+        }
+        let program = std::fs::read_to_string(&path).unwrap();
+        let ast = parse(&program, false).unwrap();
+        let formatted = format_program(&ast);
+        let formatted_ast = parse(&formatted, false).unwrap();
+        assert_eq!(format!("{formatted_ast:?}"), format!("{ast:?}"), "{path:?}");
+        assert_eq!(format_program(&formatted_ast), formatted, "{path:?}");
+    }
+}
+
+#[test]
+fn formatting_keeps_the_grouping() {
+    let program = "// dropped
+    func main(): void { x=(1+2)*3-(4-5); y = NOT (true AND false) == (NOT true);
+    if(x>1){print(x);}else if (x < 0) print(0 - x); else { z = 'say \"hi\"'; } }";
+    let ast = parse(program, false).unwrap();
+    let expected = "func main(): void {
+  x = (1 + 2) * 3 - (4 - 5);
+  y = NOT (true AND false) == (NOT true);
+  if (x > 1) {
+    print(x);
+  } else if (x < 0) {
+    print(0 - x);
+  } else {
+    z = 'say \"hi\"';
+  }
+}
+";
+    assert_eq!(format_program(&ast), expected);
+}
+
+#[test]
+fn run_capture_keeps_the_output_before_an_error() {
+    let program = "func main(): void { print(\"before\"); x = 1 / 0; print(\"after\"); }";
+    let quad_manager = compile(program).unwrap();
+    let mut vm = VM::new(&quad_manager, false, false);
+    let (output, res) = vm.run_capture();
+    assert_eq!(output, "before\n");
+    assert_eq!(res, Err("Attempt to divide by zero".to_owned()));
+    // Each run starts from a clean state
+    let mut vm = VM::new(&quad_manager, false, false);
+    assert_eq!(vm.run_capture().0, "before\n");
+}
+
+#[test]
+fn bool_as_int_print() {
+    let program = "func main(): void { print(true, false, \"true\", 1 == 1); }";
+    let quad_manager = compile(program).unwrap();
+    let mut vm = VM::new(&quad_manager, false, true);
+    assert!(vm.run().is_ok());
+    assert_eq!(vm.messages.concat(), "1 0 true 1\n");
+}
+
+#[test]
+fn int_and_float_equality() {
+    let program = "func main(): void { print(1 == 1.0, 1 == 1.5, 1 != 1.5, 2.0 == 2, 1 < 1.5); }";
+    let quad_manager = compile(program).unwrap();
+    let mut vm = VM::new(&quad_manager, false, true);
+    assert!(vm.run().is_ok());
+    assert_eq!(vm.messages.concat(), "1 0 1 1 1\n");
+}
+
+#[test]
+fn control_flow_graph() {
+    let program = "func main(): void { a = 0; while (a < 3) { a = a + 1; } print(a); }";
+    let quad_manager = compile(program).unwrap();
+    assert_eq!(
+        quad_manager.basic_blocks(),
+        vec![0..1, 1..2, 2..4, 4..7, 7..10]
+    );
+    let dot = quad_manager.to_dot();
+    let edges: Vec<_> = dot.lines().filter(|line| line.contains("->")).collect();
+    let expected = [
+        "    b0 -> b1;",
+        "    b1 -> b2;",
+        "    b2 -> b4 [label=\"false\"];",
+        "    b2 -> b3 [label=\"true\"];",
+        "    b3 -> b2;",
+    ];
+    assert_eq!(edges, expected);
+}
+
+#[test]
+fn argv_reads_program_arguments() {
+    let program = "func main(): void { print(argv(1), argv(0)); print(argv(2)); }";
+    let quad_manager = compile(program).unwrap();
+    let mut vm = VM::new(&quad_manager, false, false);
+    vm.set_args(vec!["world".to_string(), "hello".to_string()]);
+    assert!(vm.run().is_err());
+    assert_eq!(vm.messages.concat(), "hello world\n");
+}
+
+#[test]
+fn int_and_float_can_be_compared() {
+    let program = "func main(): void {
+        print(2 < 3.5, 2 <= 3.5, 2 > 3.5, 2 >= 3.5);
+        print(3.5 < 2, 3.5 <= 2, 3.5 > 2, 3.5 >= 2);
+        print(2 <= 2.0, 2.0 >= 2, 2 < 2.0, 2.0 > 2);
+    }";
+    let expected = "true true false false\nfalse false true true\ntrue true false false\n";
+    assert_eq!(eval(program).unwrap().concat(), expected);
+}
+
+#[test]
+fn strings_can_be_ordered() {
+    let program = "func main(): void {
+        print(\"ada\" < \"bob\", \"bob\" <= \"ada\", \"b\" > \"abc\", \"ada\" >= \"ada\");
+        print(\"ada\" == \"ada\", \"ada\" != \"bob\", \"ada\" == \"bob\");
+    }";
+    let expected = "true false true true\ntrue true false\n";
+    assert_eq!(eval(program).unwrap().concat(), expected);
+}
+
+#[test]
+fn to_string_accepts_every_atomic_type() {
+    let program = "func main(): void {
+        values = [to_string(3), to_string(2.5), to_string(true), to_string(\"raoul\")];
+        print(values[0], values[1], values[2], values[3]);
+        print(to_string(1 > 2) == \"false\", to_string(-4) == \"-4\");
+    }";
+    let expected = "3 2.5 true raoul\ntrue true\n";
+    assert_eq!(eval(program).unwrap().concat(), expected);
+}
+
+#[test]
+fn printjoin_uses_the_given_separator() {
+    let program = "func main(): void {
+        printjoin(\",\", 1, 2.5, \"x\", true);
+        printjoin(\"\", 1, 2, 3);
+        printjoin(\";\");
+    }";
+    assert_eq!(eval(program).unwrap().concat(), "1,2.5,x,true\n123\n\n");
+}
+
+#[test]
+fn plots_do_not_stop_the_program() {
+    let program = "func main(): void {
+        data = read_csv(\"src/examples/data/small data.csv\");
+        plot(data, \"age\", \"height\");
+        print(1);
+        histogram(data, \"age\", 2);
+        plot(data, \"height\", \"age\");
+        print(2);
+    }";
+    assert_eq!(eval(program).unwrap().concat(), "1\n2\n");
+}
+
+#[test]
+fn dataframe_shape_in_conditions() {
+    let program = "func main(): void {
+        data = read_csv(\"src/examples/data/small data.csv\");
+        if (get_rows(data) > 100) {
+            print(\"big\");
+        } else {
+            print(\"small\");
+        }
+        if (get_rows(data) == 0) {
+            print(\"empty\");
+        }
+        cells = get_rows(data) * get_columns(data);
+        print(cells, get_columns(data) - 1 >= 2);
+    }";
+    let quad_manager = compile(program).unwrap();
+    let operators: Vec<_> = quad_manager.quads().iter().map(|q| q.operator).collect();
+    assert!(operators.contains(&Operator::Gt));
+    assert!(operators.contains(&Operator::GotoF));
+    assert_eq!(eval(program).unwrap().concat(), "small\n9 true\n");
+}
+
+#[test]
+fn histogram_density_option() {
+    let program = "func main(): void {
+        data = read_csv(\"src/examples/data/small data.csv\");
+        histogram(data, \"age\", 2);
+        histogram(data, \"age\", 2, true);
+        print(1);
+    }";
+    let quad_manager = compile(program).unwrap();
+    let operators: Vec<_> = quad_manager.quads().iter().map(|q| q.operator).collect();
+    let count = |operator| operators.iter().filter(|&&op| op == operator).count();
+    assert_eq!(count(Operator::Histogram), 2);
+    assert_eq!(count(Operator::Density), 1);
+    // Only the histogram with a density is preceded by it
+    let second = operators.iter().rposition(|&op| op == Operator::Histogram);
+    assert_eq!(operators[second.unwrap() - 1], Operator::Density);
+    assert_eq!(eval(program).unwrap().concat(), "1\n");
+
+    let program = "func main(): void {
+        data = read_csv(\"src/examples/data/small data.csv\");
+        histogram(data, \"age\", 2, \"yes\");
+    }";
+    assert!(compile(program).is_err());
+}
+
+#[test]
+fn short_circuit_skips_the_right_side() {
+    let program = "func main(): void {
+        data = read_csv(\"src/examples/data/empty column.csv\");
+        has_scores = null_count(data, \"score\") < get_rows(data);
+        if (has_scores AND 10 / average(data, \"score\") > 1) {
+            print(\"high\");
+        }
+        if (NOT has_scores OR 10 / average(data, \"score\") > 1) {
+            print(\"skipped\");
+        }
+        print(true AND 0, false OR 1);
+        print(10 / average(data, \"score\"));
+    }";
+    let quad_manager = compile(program).unwrap();
+    let mut vm = VM::new(&quad_manager, false, false);
+    // Only the unguarded division reaches the empty column
+    assert!(vm.run_collect().is_err());
+    assert_eq!(vm.messages.concat(), "skipped\nfalse true\n");
+}
+
+#[test]
+fn dataframe_reductions_compose_in_expressions() {
+    let program = "func main(): void {
+        data = read_csv(\"src/examples/data/small data.csv\");
+        print(average(data, \"age\") + 1, 2 * correlation(data, \"age\", \"height\"));
+        print(get_rows(data) - get_columns(data), null_count(data, \"age\") == 0);
+        if (average(data, \"age\") > median(data, \"height\")) {
+            print(\"greater\");
+        }
+        spread = max(data, \"age\") - min(data, \"age\") == range(data, \"age\");
+        print(spread);
+    }";
+    let output = eval(program).unwrap().concat();
+    assert_eq!(output, "21 2\n0 true\ngreater\ntrue\n");
+}
+
+#[test]
+fn dataframe_ops_emit_their_quads() {
+    let program = "func main(): void {
+        data = read_csv(\"src/examples/data/small data.csv\");
+        a = get_rows(data) + average(data, \"age\");
+        b = correlation(data, \"age\", \"height\");
+        plot(data, \"age\", \"height\");
+        histogram(data, \"age\", 2);
+    }";
+    let quad_manager = compile(program).unwrap();
+    let operators: Vec<_> = quad_manager.quads().iter().map(|q| q.operator).collect();
+    for operator in [
+        Operator::ReadCSV,
+        Operator::Rows,
+        Operator::Average,
+        Operator::Corr,
+        Operator::Plot,
+        Operator::Histogram,
+    ] {
+        assert!(operators.contains(&operator), "{operator:?}");
+    }
+    assert!(eval(program).is_ok());
+}
+
+#[test]
+fn first_and_last_values_of_a_column() {
+    let program = |column: &str| {
+        format!(
+            "func main(): void {{
+                data = read_csv(\"src/examples/data/missing values.csv\");
+                print(first(data, \"age\"), last(data, \"age\"));
+                print(first(data, \"{column}\"));
+            }}"
+        )
+    };
+    let source = program("age");
+    assert_eq!(eval(&source).unwrap().concat(), "10 40\n10\n");
+
+    let source = program("height");
+    let error = eval(&source).unwrap_err();
+    assert_eq!(error, "Column \"height\" has no value in row 0");
+}
+
+#[test]
+fn rows_of_the_min_and_max_values() {
+    let program = |column: &str| {
+        format!(
+            "func main(): void {{
+                data = read_csv(\"src/examples/data/missing values.csv\");
+                print(argmin(data, \"age\"), argmax(data, \"age\"));
+                print(argmin(data, \"height\"), argmax(data, \"height\"));
+                row = argmax(data, \"{column}\") + 1;
+                print(row);
+            }}"
+        )
+    };
+    let source = program("age");
+    assert_eq!(eval(&source).unwrap().concat(), "0 3\n1 3\n4\n");
+
+    let source = program("name");
+    let error = eval(&source).unwrap_err();
+    assert_eq!(error, "Column \"name\" is not numeric");
+
+    let program = "func main(): void {
+        data = read_csv(\"src/examples/data/empty column.csv\");
+        print(argmin(data, \"score\"));
+    }";
+    let error = eval(program).unwrap_err();
+    assert_eq!(error, "Column \"score\" has no values");
+}
+
+#[test]
+fn crlf_line_endings_and_bom() {
+    let program = "func main(): void { print(1, 2); print(); }";
+    let quad_manager = compile(program).unwrap();
+    let mut vm = VM::new(&quad_manager, false, false);
+    vm.set_crlf(true);
+    vm.set_bom(true);
+    assert_eq!(vm.run_collect().unwrap(), "\u{feff}1 2\r\n\r\n");
+}
+
+#[test]
+fn empty_print_writes_a_new_line() {
+    let program = "func main(): void { print(); print(1); print(); }";
+    assert_eq!(eval(program).unwrap().concat(), "\n1\n\n");
+}
+
+#[test]
+fn random_is_reproducible_with_a_seed() {
+    let program = "func main(): void { for (i = 0 to 50) { n = random_int(-2, 2); f = random(); assert(n >= -2 AND n <= 2); assert(f >= 0.0 AND f < 1.0); print(n, f); } }";
+    let quad_manager = compile(program).unwrap();
+    let run = |seed| {
+        let mut vm = VM::new(&quad_manager, false, false);
+        vm.set_seed(seed);
+        vm.run_collect().unwrap()
+    };
+    assert_eq!(run(42), run(42));
+    assert_ne!(run(42), run(7));
+}
+
+#[test]
+fn random_int_rejects_an_empty_range() {
+    let program = "func main(): void { print(random_int(3, 2)); }";
+    assert!(eval(program).is_err());
+}
+
+#[test]
+fn quads_annotated_with_source_lines() {
+    let program = "func main(): void {\n  a = 1;\n  if (a > 0) {\n    print(a);\n  }\n}";
+    let quad_manager = compile(program).unwrap();
+    let listing = quad_manager.annotated();
+    let comments: Vec<_> = listing
+        .lines()
+        .filter(|line| line.trim_start().starts_with("//"))
+        .map(str::trim)
+        .collect();
+    assert_eq!(
+        comments,
+        ["// 2: a = 1;", "// 3: if (a > 0) {", "// 4: print(a);"]
+    );
+}
+
+#[test]
+fn nan_is_only_different() {
+    // Doubling stops changing the value once it overflows to infinity
+    let program = "func main(): void {
+        inf = 2.0;
+        while (inf < inf * 2.0) {
+            inf = inf * 2.0;
+        }
+        nan = inf - inf;
+        print(nan == nan, nan != nan, nan < 1.0, nan >= 1.0, nan != 1.0);
+        print(\"a\" == \"a\", \"a\" != \"a\", \"a\" != \"b\");
+    }";
+    let quad_manager = compile(program).unwrap();
+    let mut vm = VM::new(&quad_manager, false, true);
+    assert!(vm.run().is_ok());
+    assert_eq!(vm.messages.concat(), "0 1 0 0 1\n1 0 1\n");
+}
+
+#[test]
+fn read_with_prompt() {
+    let program = "func main(): void { a = input(\"Age: \"); b = input(); }";
+    let quad_manager = compile(program).unwrap();
+    let operators: Vec<_> = quad_manager.quads().iter().map(|q| q.operator).collect();
+    let expected = [
+        Operator::Goto,
+        Operator::Print,
+        Operator::Read,
+        Operator::Assignment,
+        Operator::Read,
+        Operator::Assignment,
+        Operator::End,
+    ];
+    assert_eq!(operators, expected);
+}
+
+#[test]
+fn increment_statements_emit_inc_and_dec() {
+    let program = "func main(): void {
+        i = 0;
+        i++;
+        x = 1.5;
+        x--;
+        arr = [10, 20];
+        arr[i]--;
+        arr[0]++;
+        print(i, x, arr[0], arr[1]);
+    }";
+    let quad_manager = compile(program).unwrap();
+    let count = |operator| {
+        let quads = quad_manager.quads().iter();
+        quads.filter(|quad| quad.operator == operator).count()
+    };
+    assert_eq!(count(Operator::Inc), 2);
+    assert_eq!(count(Operator::Dec), 2);
+    let output = eval(program).unwrap().concat();
+    assert_eq!(output, "1 0.5 11 19\n");
+
+    let program = "func main(): void { s = \"a\"; s++; }";
+    assert!(compile(program).is_err());
+}
+
+#[test]
+fn watchdog_reports_where_it_triggered() {
+    let program = "func fib(n: int): int {
+        return fib(n - 1) + fib(n - 2);
+    }
+
+    func main(): void {
+        print(fib(10));
+    }";
+    let quad_manager = compile(program).unwrap();
+    let era = quad_manager
+        .quads()
+        .iter()
+        .position(|quad| quad.operator == Operator::Era)
+        .unwrap();
+    let error = VM::new(&quad_manager, false, false).run().unwrap_err();
+    let expected = format!("Stack overflow in function `fib` at quad {era} (line 2)");
+    assert_eq!(error, expected);
+
+    let mut vm = VM::new(&quad_manager, false, false);
+    vm.set_stack_cap(8);
+    assert!(vm
+        .run()
+        .unwrap_err()
+        .starts_with("Stack overflow in function `fib`"));
+
+    let program = "func main(): void {
+        i = 0;
+        while (true) {
+            i++;
+        }
+    }";
+    let quad_manager = compile(program).unwrap();
+    let mut vm = VM::new(&quad_manager, false, false);
+    vm.set_max_steps(50);
+    let error = vm.run().unwrap_err();
+    assert!(error.starts_with("Exceeded the limit of 50 steps in function `main` at quad"));
+}
+
+#[test]
+fn repeat_uses_a_hidden_counter() {
+    let program = "func main(): void { repeat 3 { print(1); } }";
+    let quad_manager = compile(program).unwrap();
+    let operators: Vec<_> = quad_manager.quads().iter().map(|q| q.operator).collect();
+    let expected = [
+        Operator::Goto,
+        Operator::Assignment,
+        Operator::Assignment,
+        Operator::Lt,
+        Operator::GotoF,
+        Operator::Print,
+        Operator::PrintNl,
+        Operator::Inc,
+        Operator::Goto,
+        Operator::End,
+    ];
+    assert_eq!(operators, expected);
+    let output = eval(program).unwrap().concat();
+    assert_eq!(output, "1\n1\n1\n");
+
+    // Counts that aren't constant are verified while running
+    let program = "func main(): void { n = 2; repeat n { n = 0 - 5; print(n); } repeat n {} }";
+    let quad_manager = compile(program).unwrap();
+    let quads = quad_manager.quads().iter();
+    assert_eq!(
+        quads.filter(|q| q.operator == Operator::VerCount).count(),
+        2
+    );
+    let mut vm = VM::new(&quad_manager, false, false);
+    let error = vm.run_collect().unwrap_err();
+    assert_eq!(error, "Can't repeat a loop a negative amount of times (-5)");
+    assert_eq!(vm.messages, vec!["-5", "\n", "-5", "\n"]);
+}
+
+#[test]
+fn negative_indices_count_from_the_end() {
+    let program = "func main(): void {
+        arr = [1, 2, 3];
+        i = 0 - 1;
+        print(arr[-1], arr[-3], arr[i], arr[i - 1]);
+        arr[-1] = 9;
+        mat = declare_arr<int>(2, 3);
+        mat[-1][-1] = 7;
+        print(arr[2], mat[1][2], mat[i][i]);
+        print(arr[i - 3]);
+    }";
+    let quad_manager = compile(program).unwrap();
+    let mut vm = VM::new(&quad_manager, false, false);
+    assert_eq!(
+        vm.run_collect(),
+        Err("Index out of range for array".to_owned())
+    );
+    assert_eq!(vm.messages.concat(), "3 1 3 2\n9 7 7\n");
+}
+
+#[test]
+fn for_each_copies_every_element() {
+    let program = "func main(): void {
+        arr = [1, 2, 3];
+        total = 0;
+        for x in arr {
+            total = total + x;
+            x = 0;
+        }
+        print(total, arr[0], arr[1], arr[2]);
+    }";
+    let quad_manager = compile(program).unwrap();
+    let count = |operator| {
+        let quads = quad_manager.quads().iter();
+        quads.filter(|quad| quad.operator == operator).count()
+    };
+    // The loop checks its bounds once, apart from the literal and the print
+    assert_eq!(count(Operator::Ver), 3 + 1 + 3);
+    assert_eq!(count(Operator::Inc), 1);
+    let output = eval(program).unwrap().concat();
+    assert_eq!(output, "6 1 2 3\n");
+
+    let program = "func main(): void { n = 1; for x in n { print(x); } }";
+    assert!(compile(program).is_err());
+}
+
+#[test]
+fn read_bool_is_a_condition() {
+    let program = "func main(): void { count = 0; while (read_bool()) { count = count + 1; } if (read_bool(\"Again? \")) { print(count); } }";
+    let quad_manager = compile(program).unwrap();
+    let mut vm = VM::new(&quad_manager, false, false);
+    let lines = ["true", "true", "false", "true"];
+    vm.set_input(lines.iter().map(|line| line.to_string()).collect());
+    assert!(vm.run().is_ok());
+    assert_eq!(vm.messages, vec!["Again? ", "2", "\n"]);
+
+    let mut vm = VM::new(&quad_manager, false, false);
+    vm.set_input(vec!["yes".to_owned()]);
+    assert!(vm.run().is_err());
+}
+
+#[test]
+fn operators_show_their_source_symbol() {
+    assert_eq!(Operator::Lte.symbol(), "<=");
+    assert_eq!(Operator::And.symbol(), "AND");
+    assert_eq!(Operator::Assignment.symbol(), "=");
+    assert_eq!(Operator::Average.symbol(), "average");
+    assert_eq!(Operator::GoSub.symbol(), "GoSub");
+    let quad = Quadruple::new_com(Operator::Lte, 20_000, 30_000, 20_001);
+    assert_eq!(format!("{quad:?}"), "<=          T0    C0    T1");
+}
+
+#[test]
+fn deep_expression_reuses_temps() {
+    let program = "func main(): void { a = 1; b = ((((a + 2) * 3) - 4) / 5); print(b); }";
+    let quad_manager = compile(program).unwrap();
+    let main = quad_manager.dir_func.functions.get(&("main".to_owned(), 0));
+    assert_eq!(main.unwrap().temp_addresses.size(), 1);
+}
+
+#[test]
+fn optimize_reuses_repeated_expressions() {
+    let program = "func main(): void {
+        a = 2;
+        b = 3;
+        c = (a + b) * (a + b);
+        print(c, a < b, a < b);
+        a = 5;
+        print(a + b, a + b);
+    }";
+    let count_sums = |quad_manager: &QuadrupleManager| {
+        let quads = quad_manager.quads().iter();
+        quads.filter(|quad| quad.operator == Operator::Sum).count()
+    };
+    let plain = compile(program).unwrap();
+    let optimized = compile_with(
+        program,
+        CompileOptions {
+            optimize: true,
+            ..CompileOptions::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(count_sums(&plain), 4);
+    // `a + b` is computed again after `a` changes
+    assert_eq!(count_sums(&optimized), 2);
+    assert_eq!(plain.quads().len(), optimized.quads().len());
+    let plain_output = VM::new(&plain, false, false).run_collect().unwrap();
+    let optimized_output = VM::new(&optimized, false, false).run_collect().unwrap();
+    assert_eq!(plain_output, "25 true true\n8 8\n");
+    assert_eq!(optimized_output, plain_output);
+}
+
+#[test]
+fn optimize_folds_double_negations() {
+    let program = "func main(): void {
+        b = false;
+        x = 5;
+        print(NOT NOT b, NOT NOT x);
+    }";
+    let nots = |quad_manager: &QuadrupleManager| {
+        let quads = quad_manager.quads().iter();
+        quads.filter(|quad| quad.operator == Operator::Not).count()
+    };
+    let plain = compile(program).unwrap();
+    let optimized = compile_with(
+        program,
+        CompileOptions {
+            optimize: true,
+            ..CompileOptions::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(nots(&plain), 4);
+    // `NOT NOT x` is `true` for ints, so only the bool is folded
+    assert_eq!(nots(&optimized), 3);
+    let b = optimized.quads()[1].res;
+    let folded = &optimized.quads()[4];
+    assert_eq!(folded.operator, Operator::Assignment);
+    assert_eq!(folded.op_1, b);
+    assert_eq!(folded.res, plain.quads()[4].res);
+    let output = VM::new(&optimized, false, false).run_collect().unwrap();
+    assert_eq!(output, "false true\n");
+}
+
+#[test]
+fn local_arrays_dont_collide_with_temps() {
+    let program = "func squares(n: int): int {
+        values = declare_arr<int>(100);
+        i = 0;
+        while (i < 100) {
+            values[i] = i * i;
+            i = i + 1;
+        }
+        return values[n] + values[99];
+    }
+    func main(): void { print(squares(7), squares(0)); }";
+    let quad_manager = compile(program).unwrap();
+    let squares = quad_manager
+        .dir_func
+        .functions
+        .get(&("squares".to_owned(), 1));
+    let squares = squares.unwrap();
+    // The array, `n` and `i`
+    assert_eq!(squares.local_addresses.size(), 102);
+    assert_ne!(
+        squares.local_addresses.get_base(),
+        squares.temp_addresses.get_base()
+    );
+    assert_eq!(eval(program).unwrap().concat(), "9850 9801\n");
+}
+
+#[test]
+fn calls_fit_arrays_as_large_as_a_function_holds() {
+    let program = "func filled(): int {
+        values = declare_arr<int>(2499);
+        values[2498] = 7;
+        return values[2498];
+    }
+    func main(): void { print(filled()); }";
+    assert_eq!(eval(program).unwrap().concat(), "7\n");
+}
+
+#[test]
+fn function_errors_tell_missing_from_void() {
+    let error = |body: &str| {
+        let program = format!("func greet(): void {{ print(1); }} func main(): void {{ {body} }}");
+        eval(&program).unwrap_err()
+    };
+    let undeclared = "Function \"missing\" was not declared";
+    let void = "Function \"greet\" does not return a value to be used";
+    assert!(error("missing();").contains(undeclared));
+    assert!(error("a = missing();").contains(undeclared));
+    assert!(error("a, b = missing();").contains(undeclared));
+    assert!(error("a = greet();").contains(void));
+    assert!(error("a, b = greet();").contains(void));
+    assert!(error("print(greet() + 1);").contains(void));
+    assert!(error("greet(1);").contains("Function \"greet\" takes 0 argument(s), but 1 were given"));
+    let not_callable = "`a` is a variable, so it can't be called";
+    assert!(error("a = 1; a();").contains(not_callable));
+    assert!(error("a = 1; b = a() + 1;").contains(not_callable));
+}
+
+#[test]
+fn operands_show_their_scope() {
+    let quad = Quadruple::new_com(Operator::Sum, 3, 30_001, 20_012);
+    assert_eq!(format!("{quad:?}"), "+           G3    C1    T12");
+    let (size, first_quad) = (QuadOperand::Literal(2), QuadOperand::Literal(17));
+    let quad = Quadruple::new(Operator::Era, Some(size), Some(first_quad), None);
+    assert_eq!(format!("{quad:?}"), "Era         2     17    -");
+    assert_eq!(QuadOperand::Address(40_002).raw(), 40_002);
+}
+
+#[test]
+fn eval_runs_inline_source() {
+    let messages = eval("func main(): void { print(1 + 2); }").unwrap();
+    assert_eq!(messages, vec!["3", "\n"]);
+    assert!(eval("func main(): void { a = b; }").is_err());
+    assert!(eval("func main(): void { print(1 / 0); }").is_err());
+}
+
+#[test]
+fn fail_fast_stops_at_first_error() {
+    let program = "func main(): void { a = b; c = d; }";
+    assert_eq!(compile(program).unwrap_err().len(), 2);
+    let options = CompileOptions {
+        fail_fast: true,
+        ..CompileOptions::default()
+    };
+    assert_eq!(compile_with(program, options).unwrap_err().len(), 1);
+    // The mode only applies to the compilation it was given to
+    assert_eq!(compile(program).unwrap_err().len(), 2);
+}
+
+#[test]
+fn return_inside_a_loop_keeps_the_jumps() {
+    let program = "func find(limit: int): int {
+        i = 0;
+        if (limit > 0) {
+            while (i < 100) {
+                if (i * i >= limit) {
+                    return i;
+                }
+                i = i + 1;
+            }
+        }
+        return 0 - 1;
+    }
+    func main(): void {
+        print(find(10), find(0));
+    }";
+    let quad_manager = compile(program).unwrap();
+    let quads = quad_manager.quads();
+    let jumps: Vec<_> = quads
+        .iter()
+        .enumerate()
+        .filter(|(_, quad)| quad.operator.is_goto())
+        .map(|(i, quad)| (i, quad.operator, quad.res))
+        .collect();
+    let target = |quad: usize| Some(QuadOperand::Literal(quad));
+    assert_eq!(
+        jumps,
+        vec![
+            // To main, after the EndProc of find
+            (0, Operator::Goto, target(16)),
+            // The if and the while skip to the final return
+            (3, Operator::GotoF, target(13)),
+            (5, Operator::GotoF, target(13)),
+            // The inner if skips its return
+            (8, Operator::GotoF, target(10)),
+            // Back to the condition of the while
+            (12, Operator::Goto, target(4)),
+        ]
+    );
+    assert_eq!(quads[9].operator, Operator::Return);
+    assert_eq!(quads[15].operator, Operator::EndProc);
+    let output = eval(program).unwrap().concat();
+    assert_eq!(output, "4 -1\n");
 }
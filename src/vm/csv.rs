@@ -0,0 +1,60 @@
+//! polars-io 0.21 writes the quoted fields it reads past the length of its
+//! buffer, which is undefined behaviour. Files with quotes are rewritten
+//! without them before polars reads them.
+
+/// Delimiters tried, in order, to separate the fields of an unquoted file
+const DELIMITERS: [u8; 5] = [b',', b';', b'\t', b'|', 0x1f];
+
+/// Fields of each line, without the quotes wrapping them and with doubled
+/// quotes unescaped. `None` if a quoted field isn't closed in its line.
+fn records(source: &[u8]) -> Option<Vec<Vec<Vec<u8>>>> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = Vec::new();
+    let mut quoted = false;
+    let mut bytes = source.iter().copied().peekable();
+    while let Some(byte) = bytes.next() {
+        match (quoted, byte) {
+            (true, b'"') if bytes.peek() == Some(&b'"') => {
+                bytes.next();
+                field.push(b'"');
+            }
+            (true, b'"') => quoted = false,
+            (true, b'\n') => return None,
+            (false, b'"') if field.is_empty() => quoted = true,
+            (false, b',') => record.push(std::mem::take(&mut field)),
+            (false, b'\n') => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            (_, byte) => field.push(byte),
+        }
+    }
+    if quoted {
+        return None;
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Some(records)
+}
+
+/// The CSV without quotes, along with the delimiter separating its fields,
+/// which none of them contains. `None` if there's no such delimiter or a
+/// quoted field spans several lines.
+pub fn unquote(source: &[u8]) -> Option<(Vec<u8>, u8)> {
+    let records = records(source)?;
+    let delimiter = DELIMITERS.into_iter().find(|delimiter| {
+        records
+            .iter()
+            .flatten()
+            .all(|field| !field.contains(delimiter))
+    })?;
+    let mut unquoted = Vec::new();
+    for record in records {
+        unquoted.extend(record.join(&delimiter));
+        unquoted.push(b'\n');
+    }
+    Some((unquoted, delimiter))
+}
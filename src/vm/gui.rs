@@ -5,94 +5,170 @@ use egui::{
 };
 use polars::prelude::DataFrame;
 
-enum AppType {
+use super::VMResult;
+
+#[derive(Debug)]
+enum ChartType {
     Plot,
     Histogram,
 }
 
-pub struct App {
-    app_type: AppType,
+/// Bar of a histogram, counting the values from `start` up to `start + width`
+#[derive(Debug, PartialEq)]
+pub struct Bin {
+    pub start: f64,
+    pub width: f64,
+    pub count: usize,
+}
+
+/// Splits the range of the values into `bins` bins of the same width, the
+/// last one also holding the max. When every value is the same the bins are
+/// 1 wide, starting at it. Missing values are skipped.
+pub fn histogram_bins(values: impl Iterator<Item = Option<f64>>, bins: usize) -> Vec<Bin> {
+    let values: Vec<f64> = values.flatten().collect();
+    let min = values.iter().copied().reduce(f64::min);
+    let max = values.iter().copied().reduce(f64::max);
+    let (min, max) = match (min, max) {
+        (Some(min), Some(max)) => (min, max),
+        _ => return Vec::new(),
+    };
+    let step = match (max - min) / bins as f64 {
+        step if step > 0.0 => step,
+        _ => 1.0,
+    };
+    let mut counts = vec![0; bins];
+    for value in values {
+        let index = ((value - min) / step).floor() as usize;
+        counts[index.min(bins - 1)] += 1;
+    }
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(index, count)| Bin {
+            start: min + index as f64 * step,
+            width: step,
+            count,
+        })
+        .collect()
+}
+
+/// A single plot or histogram
+#[derive(Debug)]
+pub struct Chart {
+    chart_type: ChartType,
     bins: Option<usize>,
     data: DataFrame,
+    /// Whether a histogram shows the share of the values in each bar, so they
+    /// add up to 1, instead of their count
+    density: bool,
     line_style: LineStyle,
 }
 
-impl App {
-    fn new(data: DataFrame, app_type: AppType, bins: Option<usize>) -> Self {
+impl Chart {
+    fn new(data: DataFrame, chart_type: ChartType, bins: Option<usize>, density: bool) -> Self {
         Self {
-            app_type,
+            chart_type,
             data,
+            density,
             line_style: LineStyle::dotted_loose(),
             bins,
         }
     }
 
     pub fn new_plot(data: DataFrame) -> Self {
-        App::new(data, AppType::Plot, None)
+        Chart::new(data, ChartType::Plot, None, false)
     }
 
-    pub fn new_histogram(data: DataFrame, bins: usize) -> Self {
-        App::new(data, AppType::Histogram, Some(bins))
+    pub fn new_histogram(data: DataFrame, bins: usize, density: bool) -> Self {
+        Chart::new(data, ChartType::Histogram, Some(bins), density)
     }
 
     fn plot_line(&self) -> Line {
         let column_1 = self.data["column_1"].f64().unwrap();
         let column_2 = self.data["column_2"].f64().unwrap();
-        let iter = column_1
-            .into_iter()
-            .zip(column_2.into_iter())
-            .map(|(x, y)| {
-                let x: f64 = x.unwrap();
-                let y: f64 = y.unwrap();
-                Value::new(x, y)
-            });
+        let iter = column_1.into_iter().zip(column_2).map(|(x, y)| {
+            let x: f64 = x.unwrap();
+            let y: f64 = y.unwrap();
+            Value::new(x, y)
+        });
         Line::new(Values::from_values_iter(iter))
             .color(Color32::BLUE)
             .style(self.line_style)
     }
 
     fn plot_histogram(&self) -> BarChart {
-        let bins = self.bins.unwrap() + 1;
-        let mut data: Vec<(f64, f64)> = vec![(0.0, f64::MAX); bins];
-        let column = &self.data["column"];
-        let min = column.min::<f64>().unwrap();
-        let max = column.max::<f64>().unwrap();
-        let f64_bins = bins.to_string().parse::<f64>().unwrap();
-        let step = (max - min) / f64_bins;
-        let chunked_arr = column.f64().unwrap();
-        chunked_arr.into_iter().for_each(|v| {
-            let value = v.unwrap();
-            let index: usize = match (value - min) / step {
-                x if x >= f64_bins => bins - 1,
-                x => x.floor().to_string().parse().unwrap(),
-            };
-            let (count, start) = data.get_mut(index).unwrap();
-            *count += 1.0;
-            if *start > value {
-                *start = value;
-            }
-        });
-        let bars: Vec<Bar> = data
-            .windows(2)
-            .map(|v| {
-                let (count, start) = v[0];
-                let limit = v[1].1;
-                Bar::new(start, count).width((limit - start) * 0.95)
+        let values = self.data["column"].f64().unwrap().into_iter();
+        let bins = histogram_bins(values, self.bins.unwrap());
+        let total = match self.density {
+            true => bins.iter().map(|bin| bin.count).sum::<usize>() as f64,
+            false => 1.0,
+        };
+        let bars: Vec<Bar> = bins
+            .iter()
+            .map(|bin| {
+                // Bars are placed by their center
+                let center = bin.start + bin.width / 2.0;
+                Bar::new(center, bin.count as f64 / total).width(bin.width * 0.95)
             })
             .collect();
         BarChart::new(bars)
     }
 
+    fn title(&self, index: usize) -> String {
+        match self.chart_type {
+            ChartType::Plot => format!("Plot {}", index + 1),
+            ChartType::Histogram => format!("Histogram {}", index + 1),
+        }
+    }
+
     fn ui(&self, ui: &mut Ui) -> InnerResponse<()> {
-        Plot::new("raoul").show(ui, |plot_ui| match self.app_type {
-            AppType::Plot => plot_ui.line(self.plot_line()),
-            AppType::Histogram => plot_ui.bar_chart(self.plot_histogram()),
+        Plot::new("raoul").show(ui, |plot_ui| match self.chart_type {
+            ChartType::Plot => plot_ui.line(self.plot_line()),
+            ChartType::Histogram => plot_ui.bar_chart(self.plot_histogram()),
         })
     }
 }
 
+/// Window with every chart requested by the program, one tab for each
+pub struct App {
+    charts: Vec<Chart>,
+    selected: usize,
+}
+
+impl App {
+    pub fn new(charts: Vec<Chart>) -> Self {
+        Self {
+            charts,
+            selected: 0,
+        }
+    }
+}
+
 impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, _: &mut eframe::Frame) {
-        egui::CentralPanel::default().show(ctx, |ui| self.ui(ui));
+        if self.charts.len() > 1 {
+            egui::TopBottomPanel::top("tabs").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for (index, chart) in self.charts.iter().enumerate() {
+                        ui.selectable_value(&mut self.selected, index, chart.title(index));
+                    }
+                })
+            });
+        }
+        let chart = &self.charts[self.selected];
+        egui::CentralPanel::default().show(ctx, |ui| chart.ui(ui));
     }
 }
+
+/// Opens a window showing the app until it gets closed. Depending on the
+/// version of eframe, `run_native` either exits the process when the window
+/// closes or returns, in which case the program keeps running.
+#[allow(unreachable_code)]
+pub fn show(app: App) -> VMResult<()> {
+    eframe::run_native(
+        "Raoul",
+        eframe::NativeOptions::default(),
+        Box::new(|_cc| Box::new(app)),
+    );
+    Ok(())
+}
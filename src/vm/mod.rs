@@ -1,28 +1,39 @@
+mod csv;
 mod gui;
+mod random;
 
-use std::{cmp::Ordering, collections::HashMap};
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    collections::{HashMap, VecDeque},
+    io::{stdout, Cursor, Write},
+};
 
 use polars::{
     datatypes::{AnyValue, DataType},
-    io::SerReader,
-    prelude::{DataFrame, Series},
+    io::{csv::CsvReader, SerReader},
+    prelude::{DataFrame, FillNullStrategy, Series},
 };
 use polars_lazy::prelude::{col, pearson_corr, IntoLazy};
 
 use crate::{
-    address::{Address, ConstantMemory, Memory, PointerMemory, TOTAL_SIZE},
+    address::{Address, ConstantMemory, Memory, PointerMemory, Scope, TOTAL_SIZE},
     dir_func::{function::Function, variable_value::VariableValue},
     enums::Operator,
-    quadruple::{quadruple::Quadruple, quadruple_manager::QuadrupleManager},
+    quadruple::{bytecode::Bytecode, quadruple::Quadruple, quadruple_manager::QuadrupleManager},
 };
 
-use self::gui::App;
+use self::{
+    gui::{App, Chart},
+    random::Random,
+};
 
 #[derive(Clone, Debug)]
 pub struct VMContext {
     address: usize,
     args: Vec<usize>,
     local_memory: Memory,
+    name: String,
     quad_pos: usize,
     size: usize,
     temp_memory: Memory,
@@ -40,6 +51,7 @@ impl VMContext {
             address,
             args,
             local_memory,
+            name: function.name.clone(),
             quad_pos,
             size,
             temp_memory,
@@ -47,29 +59,62 @@ impl VMContext {
     }
 }
 
-pub type VMResult<T> = std::result::Result<T, &'static str>;
+pub type VMResult<T> = std::result::Result<T, String>;
 
 #[derive(Debug)]
 pub struct VM {
+    args: Vec<String>,
+    bom: bool,
+    bool_as_int: bool,
     call_stack: Vec<VMContext>,
+    charts: Vec<Chart>,
     constant_memory: ConstantMemory,
     contexts_stack: Vec<VMContext>,
+    crlf: bool,
     debug: bool,
+    flush: bool,
     functions: HashMap<usize, Function>,
     global_memory: Memory,
+    handlers: Vec<Handler>,
     pointer_memory: PointerMemory,
+    /// Everything printed by the program, in order
     pub messages: Vec<String>,
+    output: Option<String>,
+    input: Option<VecDeque<String>>,
+    max_steps: Option<usize>,
     quad_list: Vec<Quadruple>,
+    /// Source line of each quad, used to tell where the watchdog triggered
+    quad_lines: Vec<Option<usize>>,
+    random: Random,
+    stack_cap: usize,
     stack_size: usize,
+    steps: usize,
     data_frame: Option<DataFrame>,
+    float_columns: HashMap<String, Series>,
+    split_parts: Vec<String>,
+    /// Whether the next histogram shows densities instead of counts
+    density: bool,
+}
+
+/// Where to resume when an error happens inside a `try`, and how deep the
+/// stacks were when it was entered
+#[derive(Debug)]
+struct Handler {
+    quad_pos: usize,
+    contexts: usize,
+    calls: usize,
+    stack_size: usize,
 }
 
-const STACK_SIZE_CAP: usize = 1024;
+/// Room for main and a call it makes to use every local and temp address
+/// they have
+const STACK_SIZE_CAP: usize = 4 * TOTAL_SIZE;
+const SHOW_ROWS: usize = 5;
 
 fn cast_to_f64(v: &AnyValue) -> f64 {
     match v {
         AnyValue::Float64(v) => *v,
-        AnyValue::Float32(v) => (*v).try_into().unwrap(),
+        AnyValue::Float32(v) => (*v).into(),
         AnyValue::Int64(v) => v.to_string().parse::<f64>().unwrap(),
         _ => unreachable!(),
     }
@@ -78,7 +123,7 @@ fn cast_to_f64(v: &AnyValue) -> f64 {
 fn safe_address(value: &Option<VariableValue>) -> VMResult<VariableValue> {
     match value {
         Some(v) => Ok(v.clone()),
-        None => Err("Found initialized value"),
+        None => Err("Found initialized value".to_owned()),
     }
 }
 
@@ -93,38 +138,107 @@ fn max(c: &Series) -> f64 {
 }
 
 impl VM {
-    pub fn new(quad_manager: &QuadrupleManager, debug: bool) -> Self {
-        let constant_memory = quad_manager.memory.clone();
-        let functions = quad_manager.dir_func.functions.clone();
-        let global_fn = quad_manager.dir_func.global_fn.clone();
-        let pointer_memory = quad_manager.pointer_memory.clone();
-        let global_memory = Memory::new(&global_fn.addresses);
-        let quad_list = quad_manager.quad_list.clone();
-        let main_function = functions.get("main").unwrap();
+    pub fn new(quad_manager: &QuadrupleManager, debug: bool, bool_as_int: bool) -> Self {
+        Self::from_bytecode(Bytecode::from(quad_manager), debug, bool_as_int)
+    }
+
+    pub fn from_bytecode(bytecode: Bytecode, debug: bool, bool_as_int: bool) -> Self {
+        let main_function = bytecode.main_function();
         let stack_size = main_function.size();
         let initial_context = VMContext::new(main_function);
+        let global_memory = Memory::new(&bytecode.global_addresses);
         Self {
+            args: Vec::new(),
+            input: None,
+            bom: false,
+            bool_as_int,
             call_stack: vec![],
-            constant_memory,
+            charts: Vec::new(),
+            constant_memory: bytecode.constants,
             contexts_stack: vec![initial_context],
+            crlf: false,
             data_frame: None,
             debug,
-            functions: functions
+            flush: false,
+            float_columns: HashMap::new(),
+            functions: bytecode
+                .functions
                 .into_iter()
-                .map(|(_, function)| (function.first_quad, function))
+                .map(|function| (function.first_quad, function))
                 .collect(),
             global_memory,
+            handlers: Vec::new(),
+            max_steps: None,
             messages: Vec::new(),
-            pointer_memory,
-            quad_list,
+            output: None,
+            pointer_memory: bytecode.pointer_memory,
+            quad_list: bytecode.quads,
+            quad_lines: bytecode.quad_lines,
+            random: Random::from_entropy(),
+            split_parts: Vec::new(),
+            density: false,
+            stack_cap: STACK_SIZE_CAP,
             stack_size,
+            steps: 0,
+        }
+    }
+
+    /// Lines read by `input` and `read_bool` instead of stdin
+    pub fn set_input(&mut self, lines: Vec<String>) {
+        self.input = Some(lines.into());
+    }
+
+    /// Arguments given to the program, read with `argv(i)`
+    pub fn set_args(&mut self, args: Vec<String>) {
+        self.args = args;
+    }
+
+    /// Ends lines with `\r\n` instead of `\n`
+    pub fn set_crlf(&mut self, crlf: bool) {
+        self.crlf = crlf;
+    }
+
+    /// Writes the UTF-8 byte order mark before the first output
+    pub fn set_bom(&mut self, bom: bool) {
+        self.bom = bom;
+    }
+
+    /// Flushes stdout after every print instead of only after new lines
+    pub fn set_flush(&mut self, flush: bool) {
+        self.flush = flush;
+    }
+
+    /// Seed used by `random` and `random_int`, making their values reproducible
+    pub fn set_seed(&mut self, seed: u64) {
+        self.random = Random::new(seed);
+    }
+
+    /// Most values and calls the stack can hold before overflowing
+    pub fn set_stack_cap(&mut self, stack_cap: usize) {
+        self.stack_cap = stack_cap;
+    }
+
+    /// Most quads the program can execute before being stopped
+    pub fn set_max_steps(&mut self, max_steps: usize) {
+        self.max_steps = Some(max_steps);
+    }
+
+    /// Describes a limit being hit, with the function and quad where it
+    /// happened
+    fn watchdog_error(&self, reason: &str) -> String {
+        let context = self.current_context();
+        let quad_pos = context.quad_pos;
+        let mut message = format!("{reason} in function `{}` at quad {quad_pos}", context.name);
+        if let Some(line) = self.quad_lines[quad_pos] {
+            message += &format!(" (line {line})");
         }
+        message
     }
 
     fn add_call_stack(&mut self, function: &Function) -> VMResult<()> {
         self.stack_size += function.size();
-        if self.stack_size > STACK_SIZE_CAP || self.contexts_stack.len() == STACK_SIZE_CAP {
-            return Err("Stack overflow!");
+        if self.stack_size > self.stack_cap || self.contexts_stack.len() == self.stack_cap {
+            return Err(self.watchdog_error("Stack overflow"));
         }
         self.call_stack.push(VMContext::new(function));
         Ok(())
@@ -176,12 +290,12 @@ impl VM {
     }
 
     fn get_value(&self, address: usize) -> VMResult<VariableValue> {
-        match address / TOTAL_SIZE {
-            0 => safe_address(self.global_memory.get(address)),
-            1 => safe_address(self.local_addresses().get(address)),
-            2 => safe_address(self.temp_addresses().get(address)),
-            3 => Ok(self.constant_memory.get(address).clone()),
-            _ => {
+        match Scope::from(address) {
+            Scope::Global => safe_address(self.global_memory.get(address)),
+            Scope::Local => safe_address(self.local_addresses().get(address)),
+            Scope::Temp => safe_address(self.temp_addresses().get(address)),
+            Scope::Constant => Ok(self.constant_memory.get(address).clone()),
+            Scope::Pointer => {
                 let address = self.pointer_memory.get(address);
                 self.get_value(address)
             }
@@ -189,47 +303,113 @@ impl VM {
     }
 
     fn write_value(&mut self, value: VariableValue, address: usize) -> VMResult<()> {
-        let determinant = address / TOTAL_SIZE;
-        if determinant >= 4 {
-            self.pointer_memory.write(address, value);
-            return Ok(());
-        }
-        let memory = match determinant {
-            0 => &mut self.global_memory,
-            1 => self.local_addresses_mut(),
-            2 => self.temp_addresses_mut(),
-            _ => unreachable!(),
+        let memory = match Scope::from(address) {
+            Scope::Global => &mut self.global_memory,
+            Scope::Local => self.local_addresses_mut(),
+            Scope::Temp => self.temp_addresses_mut(),
+            Scope::Pointer => {
+                self.pointer_memory.write(address, value);
+                return Ok(());
+            }
+            Scope::Constant => unreachable!(),
         };
         memory.write(address, &value)
     }
 
     fn process_assign(&mut self) -> VMResult<()> {
         let quad = self.get_current_quad();
-        let value = self.get_value(quad.op_1.unwrap())?;
-        let mut assignee = quad.res.unwrap();
+        let value = self.get_value(quad.op_1.unwrap().raw())?;
+        let mut assignee = quad.res.unwrap().raw();
         if assignee.is_pointer_address() {
             assignee = self.pointer_memory.get(assignee);
         }
         self.write_value(value, assignee)
     }
 
+    fn process_append(&mut self) -> VMResult<()> {
+        let quad = self.get_current_quad();
+        let suffix = String::from(self.get_value(quad.op_1.unwrap().raw())?);
+        let address = quad.res.unwrap().raw();
+        let memory = match Scope::from(address) {
+            Scope::Global => &mut self.global_memory,
+            Scope::Local => self.local_addresses_mut(),
+            scope => unreachable!("{scope}"),
+        };
+        memory.append(address, &suffix)
+    }
+
+    fn write_output(&mut self, text: &str) {
+        let mut text = Cow::Borrowed(text);
+        if self.crlf {
+            text = Cow::Owned(text.replace('\n', "\r\n"));
+        }
+        if self.bom {
+            self.bom = false;
+            text = Cow::Owned(format!("\u{feff}{text}"));
+        }
+        match &mut self.output {
+            Some(output) => output.push_str(&text),
+            None => {
+                print!("{text}");
+                if self.flush {
+                    stdout().flush().unwrap();
+                }
+            }
+        }
+    }
+
     fn print_message(&mut self, message: &str) {
         self.messages.push(message.to_string());
-        let separator = if message.contains('\n') { "" } else { " " };
-        print!("{message}{separator}");
+        self.write_output(message);
+    }
+
+    fn format_value(&self, value: &VariableValue) -> String {
+        match value {
+            VariableValue::Bool(value) if self.bool_as_int => i64::from(*value).to_string(),
+            value => format!("{value:?}"),
+        }
     }
 
     fn process_print(&mut self) -> VMResult<()> {
         let quad = self.get_current_quad();
-        let value = self.get_value(quad.op_1.unwrap())?;
-        self.print_message(&format!("{value:?}"));
+        let value = self.get_value(quad.op_1.unwrap().raw())?;
+        let message = self.format_value(&value);
+        self.print_message(&message);
+        if let Some(address) = quad.op_2 {
+            let separator = String::from(self.get_value(address.raw())?);
+            self.print_message(&separator);
+        }
         Ok(())
     }
 
+    fn read_line(&mut self) -> String {
+        if let Some(input) = &mut self.input {
+            return input.pop_front().unwrap_or_default();
+        }
+        // Make sure a pending prompt is shown before blocking on stdin
+        stdout().flush().unwrap();
+        VariableValue::from_stdin().into()
+    }
+
     fn process_read(&mut self) -> VMResult<()> {
         let quad = self.get_current_quad();
-        let value = VariableValue::from_stdin();
-        self.write_value(value, quad.res.unwrap())
+        let value = VariableValue::String(self.read_line());
+        self.write_value(value, quad.res.unwrap().raw())
+    }
+
+    fn process_read_bool(&mut self) -> VMResult<()> {
+        let quad = self.get_current_quad();
+        let line = self.read_line();
+        let value = match line.trim() {
+            "true" => true,
+            "false" => false,
+            _ => {
+                return Err(format!(
+                    "Expected \"true\" or \"false\" but received \"{line}\""
+                ))
+            }
+        };
+        self.write_value(VariableValue::Bool(value), quad.res.unwrap().raw())
     }
 
     fn unary_operation<F>(&mut self, f: F) -> VMResult<()>
@@ -237,9 +417,9 @@ impl VM {
         F: FnOnce(VariableValue) -> VariableValue,
     {
         let quad = self.get_current_quad();
-        let a = self.get_value(quad.op_1.unwrap())?;
+        let a = self.get_value(quad.op_1.unwrap().raw())?;
         let value = f(a);
-        self.write_value(value, quad.res.unwrap())
+        self.write_value(value, quad.res.unwrap().raw())
     }
 
     fn binary_operation<F>(&mut self, f: F) -> VMResult<()>
@@ -247,19 +427,20 @@ impl VM {
         F: FnOnce(VariableValue, VariableValue) -> VMResult<VariableValue>,
     {
         let quad = self.get_current_quad();
-        let a = self.get_value(quad.op_1.unwrap())?;
-        let b = self.get_value(quad.op_2.unwrap())?;
+        let a = self.get_value(quad.op_1.unwrap().raw())?;
+        let b = self.get_value(quad.op_2.unwrap().raw())?;
         let value = f(a, b)?;
-        self.write_value(value, quad.res.unwrap())
+        self.write_value(value, quad.res.unwrap().raw())
     }
 
     fn comparison(&mut self) -> VMResult<()> {
         let quad = self.get_current_quad();
-        let a = self.get_value(quad.op_1.unwrap())?;
-        let b = self.get_value(quad.op_2.unwrap())?;
+        let a = self.get_value(quad.op_1.unwrap().raw())?;
+        let b = self.get_value(quad.op_2.unwrap().raw())?;
         let ord = a.partial_cmp(&b);
         let res = match ord {
-            None => false,
+            // Unordered values, like NaN, are only different from anything
+            None => quad.operator == Operator::Ne,
             Some(ord) => match quad.operator {
                 Operator::Lt => ord == Ordering::Less,
                 Operator::Lte => ord != Ordering::Greater,
@@ -271,29 +452,88 @@ impl VM {
             },
         };
         let value = VariableValue::Bool(res);
-        self.write_value(value, quad.res.unwrap())
+        self.write_value(value, quad.res.unwrap().raw())
     }
 
     fn conditional_goto(&mut self, approved: bool) -> VMResult<usize> {
         let quad = self.get_current_quad();
-        let cond = self.get_value(quad.op_1.unwrap())?;
+        let cond = self.get_value(quad.op_1.unwrap().raw())?;
         let quad_pos = self.current_context().quad_pos;
         if bool::from(cond) == approved {
-            return Ok(quad.res.unwrap() - 1);
+            return Ok(quad.res.unwrap().raw() - 1);
         }
         Ok(quad_pos)
     }
 
-    fn process_inc(&mut self) -> VMResult<()> {
+    fn process_assert(&mut self) -> VMResult<()> {
+        let quad = self.get_current_quad();
+        let cond = self.get_value(quad.op_1.unwrap().raw())?;
+        if bool::from(cond) {
+            return Ok(());
+        }
+        match quad.op_2 {
+            Some(address) => {
+                let message = String::from(self.get_value(address.raw())?);
+                Err(format!("Assertion failed: {message}"))
+            }
+            None => Err("Assertion failed".to_owned()),
+        }
+    }
+
+    fn process_argv(&mut self) -> VMResult<()> {
+        let quad = self.get_current_quad();
+        let index = i64::try_from(self.get_value(quad.op_1.unwrap().raw())?)?;
+        let value = usize::try_from(index)
+            .ok()
+            .and_then(|index| self.args.get(index))
+            .ok_or(format!(
+                "Argument {index} was not given, the program received {}",
+                self.args.len()
+            ))?;
+        let value = VariableValue::String(value.clone());
+        self.write_value(value, quad.res.unwrap().raw())
+    }
+
+    fn process_random(&mut self) -> VMResult<()> {
+        let quad = self.get_current_quad();
+        let value = VariableValue::Float(self.random.next_float());
+        self.write_value(value, quad.res.unwrap().raw())
+    }
+
+    fn process_random_int(&mut self) -> VMResult<()> {
+        let quad = self.get_current_quad();
+        let low = i64::try_from(self.get_value(quad.op_1.unwrap().raw())?)?;
+        let high = i64::try_from(self.get_value(quad.op_2.unwrap().raw())?)?;
+        if low > high {
+            return Err(format!(
+                "random_int expects its lower bound to not exceed the upper one, got {low} and {high}"
+            ));
+        }
+        let value = VariableValue::Integer(self.random.next_int(low, high));
+        self.write_value(value, quad.res.unwrap().raw())
+    }
+
+    fn process_abort(&mut self) -> VMResult<()> {
+        let quad = self.get_current_quad();
+        Err(String::from(self.get_value(quad.op_1.unwrap().raw())?))
+    }
+
+    fn process_step<F>(&mut self, step: F) -> VMResult<()>
+    where
+        F: FnOnce(&VariableValue) -> VMResult<VariableValue>,
+    {
         let quad = self.get_current_quad();
-        let a = self.get_value(quad.res.unwrap())?;
-        let value = a.increase()?;
-        self.write_value(value, quad.res.unwrap())
+        let mut address = quad.res.unwrap().raw();
+        if address.is_pointer_address() {
+            address = self.pointer_memory.get(address);
+        }
+        let value = step(&self.get_value(address)?)?;
+        self.write_value(value, address)
     }
 
     fn process_era(&mut self) -> VMResult<()> {
         let quad = self.get_current_quad();
-        let first_quad = quad.op_2.unwrap();
+        let first_quad = quad.op_2.unwrap().raw();
         let function = self.get_function(first_quad).clone();
         self.add_call_stack(&function)
     }
@@ -308,6 +548,29 @@ impl VM {
     fn process_end_proc(&mut self) {
         let context = self.contexts_stack.pop().unwrap();
         self.stack_size -= context.size;
+        // A return inside a try leaves the handlers of the function behind
+        let contexts = self.contexts_stack.len();
+        self.handlers.retain(|handler| handler.contexts <= contexts);
+    }
+
+    fn process_try(&mut self) {
+        let quad = self.get_current_quad();
+        self.handlers.push(Handler {
+            quad_pos: quad.res.unwrap().raw(),
+            contexts: self.contexts_stack.len(),
+            calls: self.call_stack.len(),
+            stack_size: self.stack_size,
+        });
+    }
+
+    /// Unwinds to the innermost `try`, returning the position of its catch
+    /// block. Without one the error stops the program
+    fn catch_error(&mut self, error: String) -> VMResult<usize> {
+        let handler = self.handlers.pop().ok_or(error)?;
+        self.contexts_stack.truncate(handler.contexts);
+        self.call_stack.truncate(handler.calls);
+        self.stack_size = handler.stack_size;
+        Ok(handler.quad_pos)
     }
 
     #[inline]
@@ -321,17 +584,17 @@ impl VM {
     }
 
     fn write_value_param(&mut self, value: &VariableValue, address: usize) -> VMResult<()> {
-        let memory = match address / TOTAL_SIZE {
-            1 => &mut self.current_call_mut().local_memory,
-            val => unreachable!("{val}"),
+        let memory = match Scope::from(address) {
+            Scope::Local => &mut self.current_call_mut().local_memory,
+            scope => unreachable!("{scope}"),
         };
         memory.write(address, value)
     }
 
     fn process_param(&mut self) -> VMResult<()> {
         let quad = self.get_current_quad();
-        let value = self.get_value(quad.op_1.unwrap())?;
-        let index = quad.res.unwrap();
+        let value = self.get_value(quad.op_1.unwrap().raw())?;
+        let index = quad.res.unwrap().raw();
         let address = *self.current_call().args.get(index).unwrap();
         self.write_value_param(&value, address)
     }
@@ -343,41 +606,122 @@ impl VM {
 
     fn process_return(&mut self) -> VMResult<()> {
         let quad = self.get_current_quad();
-        let value = self.get_value(quad.op_1.unwrap())?;
-        let address = self.get_context_global_address();
-        self.write_value(value, address)?;
+        if let Some(op_1) = quad.op_1 {
+            let value = self.get_value(op_1.raw())?;
+            let address = self.get_context_global_address();
+            self.write_value(value, address)?;
+        }
         self.process_end_proc();
         Ok(())
     }
 
     fn process_ver(&mut self) -> VMResult<()> {
         let quad = self.get_current_quad();
-        let index = self.get_value(quad.op_1.unwrap())?;
-        let limit = self.get_value(quad.op_2.unwrap())?;
+        let index = self.get_value(quad.op_1.unwrap().raw())?;
+        let limit = self.get_value(quad.op_2.unwrap().raw())?;
         if limit <= index || VariableValue::Integer(0) > index {
-            return Err("Index out of range for array");
+            return Err("Index out of range for array".to_owned());
+        }
+        Ok(())
+    }
+
+    fn process_ver_count(&mut self) -> VMResult<()> {
+        let quad = self.get_current_quad();
+        let count = self.get_value(quad.op_1.unwrap().raw())?;
+        if VariableValue::Integer(0) > count {
+            return Err(format!(
+                "Can't repeat a loop a negative amount of times ({count:?})"
+            ));
         }
         Ok(())
     }
 
     fn read_csv(&mut self) -> VMResult<()> {
         let quad = self.get_current_quad();
-        let filename = String::from(self.get_value(quad.op_1.unwrap())?);
-        let res = polars::io::csv::CsvReader::from_path(&filename);
-        if res.is_err() {
-            return Err("Could not read the file");
+        let filename = String::from(self.get_value(quad.op_1.unwrap().raw())?);
+        let bytes = std::fs::read(&filename);
+        if bytes.is_err() {
+            return Err("Could not read the file".to_owned());
         }
-        let res = res.unwrap().has_header(true).finish();
+        let bytes = bytes.unwrap();
+        let res = match bytes.contains(&b'"') {
+            false => CsvReader::new(Cursor::new(bytes)).has_header(true).finish(),
+            true => match csv::unquote(&bytes) {
+                Some((unquoted, delimiter)) => CsvReader::new(Cursor::new(unquoted))
+                    .with_delimiter(delimiter)
+                    .with_quote_char(None)
+                    .has_header(true)
+                    .finish(),
+                None => return Err("File is not a valid CSV".to_owned()),
+            },
+        };
         if res.is_err() {
-            return Err("File is not a valid CSV");
+            return Err("File is not a valid CSV".to_owned());
         }
         self.data_frame = Some(res.unwrap());
+        self.float_columns.clear();
+        Ok(())
+    }
+
+    fn columns_of(&mut self) -> VMResult<()> {
+        let quad = self.get_current_quad();
+        let size = quad.op_1.unwrap().raw();
+        let address = quad.res.unwrap().raw();
+        let mut names: Vec<String> = self
+            .get_dataframe()?
+            .get_column_names()
+            .into_iter()
+            .map(String::from)
+            .collect();
+        if names.len() > size {
+            return Err(format!(
+                "The dataframe has {} columns, but the array can only hold {size}",
+                names.len()
+            ));
+        }
+        // The slots after the last column are left empty, as with `split`
+        names.resize(size, String::new());
+        names
+            .into_iter()
+            .enumerate()
+            .try_for_each(|(i, name)| self.write_value(VariableValue::String(name), address + i))
+    }
+
+    fn split(&mut self) -> VMResult<()> {
+        let quad = self.get_current_quad();
+        let text = String::from(self.get_value(quad.op_1.unwrap().raw())?);
+        let separator = String::from(self.get_value(quad.op_2.unwrap().raw())?);
+        if separator.is_empty() {
+            return Err("Cannot split using an empty separator".to_owned());
+        }
+        self.split_parts = text.split(&separator).map(String::from).collect();
         Ok(())
     }
 
+    fn split_parts(&mut self) -> VMResult<()> {
+        let quad = self.get_current_quad();
+        let size = quad.op_1.unwrap().raw();
+        let address = quad.res.unwrap().raw();
+        let mut parts = std::mem::take(&mut self.split_parts);
+        if parts.len() > size {
+            return Err(format!(
+                "The text has {} parts, but the array can only hold {size}",
+                parts.len()
+            ));
+        }
+        // The slots after the last part are left empty
+        parts.resize(size, String::new());
+        parts
+            .into_iter()
+            .enumerate()
+            .try_for_each(|(i, part)| self.write_value(VariableValue::String(part), address + i))
+    }
+
     fn get_dataframe(&self) -> VMResult<&DataFrame> {
         if self.data_frame.is_none() {
-            return Err("No data frame was created. You need to create one using `read_csv`");
+            return Err(
+                "No data frame was created. You need to create one using `read_csv`".to_owned(),
+            );
         }
         let data_frame = self.data_frame.as_ref().unwrap();
         Ok(data_frame)
@@ -392,7 +736,7 @@ impl VM {
             _ => unreachable!(),
         }
         .into();
-        self.write_value(value, quad.res.unwrap())
+        self.write_value(value, quad.res.unwrap().raw())
     }
 
     fn unary_df_operation<F>(&mut self, f: F) -> VMResult<()>
@@ -400,91 +744,298 @@ impl VM {
         F: FnOnce(&Series) -> f64,
     {
         let quad = self.get_current_quad();
-        let column_name = String::from(self.get_value(quad.op_1.unwrap())?);
+        let column_name = String::from(self.get_value(quad.op_1.unwrap().raw())?);
         let data_frame = self.get_dataframe()?;
         let column = data_frame.column(&column_name);
         if column.is_err() {
-            return Err("Dataframe key not found in file");
+            return Err("Dataframe key not found in file".to_owned());
         }
         let value = f(column.unwrap()).into();
-        self.write_value(value, quad.res.unwrap())
+        self.write_value(value, quad.res.unwrap().raw())
+    }
+
+    /// Unlike the other column operations the result is an `Int`, and the
+    /// column doesn't need to be numeric
+    fn null_count(&mut self) -> VMResult<()> {
+        let quad = self.get_current_quad();
+        let column_name = String::from(self.get_value(quad.op_1.unwrap().raw())?);
+        let count = match self.get_dataframe()?.column(&column_name) {
+            Ok(column) => column.null_count(),
+            Err(_) => return Err("Dataframe key not found in file".to_owned()),
+        };
+        let value = VariableValue::Integer(count.try_into().unwrap());
+        self.write_value(value, quad.res.unwrap().raw())
+    }
+
+    /// First value of the column, or the last one when `from_end` is set
+    fn column_value(&mut self, from_end: bool) -> VMResult<()> {
+        let quad = self.get_current_quad();
+        let column_name = String::from(self.get_value(quad.op_1.unwrap().raw())?);
+        let column = self.float_column(&column_name)?;
+        if column.is_empty() {
+            return Err(format!("Column \"{column_name}\" is empty"));
+        }
+        let row = if from_end { column.len() - 1 } else { 0 };
+        let value = match column.get(row) {
+            AnyValue::Null => {
+                return Err(format!(
+                    "Column \"{column_name}\" has no value in row {row}"
+                ));
+            }
+            value => cast_to_f64(&value),
+        };
+        self.write_value(value.into(), quad.res.unwrap().raw())
+    }
+
+    /// Row of the smallest value of the column, or of the largest one when
+    /// `largest` is set. Like `column_value` the result is an `Int`, and rows
+    /// without a value are skipped.
+    fn column_arg(&mut self, largest: bool) -> VMResult<()> {
+        let quad = self.get_current_quad();
+        let column_name = String::from(self.get_value(quad.op_1.unwrap().raw())?);
+        let column = self.float_column(&column_name)?;
+        if column.null_count() == column.len() {
+            return Err(format!("Column \"{column_name}\" has no values"));
+        }
+        let row = match largest {
+            // Missing values are already smaller than any other
+            true => column.arg_max(),
+            false => column
+                .fill_null(FillNullStrategy::MaxBound)
+                .map_err(|error| error.to_string())?
+                .arg_min(),
+        };
+        let row = row.expect("The column has values").try_into().unwrap();
+        self.write_value(VariableValue::Integer(row), quad.res.unwrap().raw())
+    }
+
+    /// The column casted to `Float64` and stored in a single chunk. Columns
+    /// are cached by name until the next `read_csv`, so repeated operations
+    /// neither cast nor rechunk them again. Text columns would become missing
+    /// values, so only the ones without any value are casted.
+    fn float_column(&mut self, name: &str) -> VMResult<Series> {
+        if let Some(column) = self.float_columns.get(name) {
+            return Ok(column.clone());
+        }
+        let not_numeric = || format!("Column \"{name}\" is not numeric");
+        let column = match self.get_dataframe()?.column(name) {
+            Ok(column)
+                if column.dtype() == &DataType::Utf8 && column.null_count() < column.len() =>
+            {
+                return Err(not_numeric())
+            }
+            Ok(column) => column.cast(&DataType::Float64),
+            Err(_) => return Err("Dataframe key not found in file".to_owned()),
+        };
+        let column = column.map_err(|_| not_numeric())?.rechunk();
+        self.float_columns.insert(name.to_owned(), column.clone());
+        Ok(column)
+    }
+
+    fn named_float_column(&mut self, name: &str, alias: &str) -> VMResult<Series> {
+        let mut column = self.float_column(name)?;
+        column.rename(alias);
+        Ok(column)
     }
 
     fn correlation(&mut self) -> VMResult<()> {
         let quad = self.get_current_quad();
-        let data_frame = self.get_dataframe()?;
-        let col_1_name = String::from(self.get_value(quad.op_1.unwrap())?);
-        let col_2_name = String::from(self.get_value(quad.op_2.unwrap())?);
-        let temp = data_frame
-            .clone()
-            .lazy()
-            .select([pearson_corr(
-                col(&col_1_name).cast(DataType::Float64),
-                col(&col_2_name).cast(DataType::Float64),
-            )
-            .alias("correlation")])
-            .collect()
-            .unwrap();
+        let col_1_name = String::from(self.get_value(quad.op_1.unwrap().raw())?);
+        let col_2_name = String::from(self.get_value(quad.op_2.unwrap().raw())?);
+        let temp = DataFrame::new(vec![
+            self.named_float_column(&col_1_name, "column_1")?,
+            self.named_float_column(&col_2_name, "column_2")?,
+        ])
+        .unwrap()
+        .lazy()
+        .select([pearson_corr(col("column_1"), col("column_2")).alias("correlation")])
+        .collect()
+        .unwrap();
         let value = cast_to_f64(&temp.column("correlation").unwrap().get(0)).into();
-        self.write_value(value, quad.res.unwrap())
+        self.write_value(value, quad.res.unwrap().raw())
     }
 
-    fn plot(&mut self) -> VMResult<()> {
+    fn cast_column(&mut self) -> VMResult<()> {
         let quad = self.get_current_quad();
+        let name = String::from(self.get_value(quad.op_1.unwrap().raw())?);
+        let type_name = String::from(self.get_value(quad.op_2.unwrap().raw())?);
+        let data_type = match type_name.as_str() {
+            "int" => DataType::Int64,
+            "float" => DataType::Float64,
+            "bool" => DataType::Boolean,
+            _ => DataType::Utf8,
+        };
         let data_frame = self.get_dataframe()?;
-        let col_1_name = String::from(self.get_value(quad.op_1.unwrap())?);
-        let col_2_name = String::from(self.get_value(quad.op_2.unwrap())?);
-        let temp = data_frame
+        if data_frame.column(&name).is_err() {
+            return Err("Dataframe key not found in file".to_owned());
+        }
+        let data_frame = data_frame
             .clone()
             .lazy()
-            .select([
-                col(&col_1_name).cast(DataType::Float64).alias("column_1"),
-                col(&col_2_name).cast(DataType::Float64).alias("column_2"),
-            ])
+            .with_column(col(&name).strict_cast(data_type))
             .collect()
-            .unwrap();
-        let app = App::new_plot(temp);
-        eframe::run_native(
-            "Raoul",
-            eframe::NativeOptions::default(),
-            Box::new(|_cc| Box::new(app)),
+            .map_err(|_| format!("Column \"{name}\" can not be cast to {type_name}"))?;
+        self.data_frame = Some(data_frame);
+        self.float_columns.remove(&name);
+        Ok(())
+    }
+
+    /// Prints count, mean, std, min and max of every numeric column
+    fn describe(&mut self) -> VMResult<()> {
+        let names: Vec<_> = self
+            .get_dataframe()?
+            .get_columns()
+            .iter()
+            .filter(|column| column.dtype().is_numeric())
+            .map(|column| column.name().to_owned())
+            .collect();
+        let mut table = format!(
+            "{:<20} {:>10} {:>14} {:>14} {:>14} {:>14}\n",
+            "column", "count", "mean", "std", "min", "max"
         );
+        for name in names {
+            let column = self.float_column(&name)?;
+            let count = column.len() - column.null_count();
+            let mean = column.mean().unwrap_or(0.0);
+            let std = cast_to_f64(&column.std_as_series().get(0));
+            table += &format!(
+                "{name:<20} {count:>10} {mean:>14.4} {std:>14.4} {:>14.4} {:>14.4}\n",
+                min(&column),
+                max(&column)
+            );
+        }
+        self.print_message(&table);
+        Ok(())
+    }
+
+    /// Prints the first rows of the dataframe as a table
+    fn show(&mut self) -> VMResult<()> {
+        let quad = self.get_current_quad();
+        let rows = match quad.op_1 {
+            Some(address) => match self.get_value(address.raw())? {
+                VariableValue::Integer(rows) if rows < 0 => {
+                    return Err("The amount of rows can't be negative".to_owned())
+                }
+                rows => usize::from(rows),
+            },
+            None => SHOW_ROWS,
+        };
+        let data_frame = self.get_dataframe()?.head(Some(rows));
+        let columns = data_frame.get_columns();
+        let cells: Vec<Vec<String>> = columns
+            .iter()
+            .map(|column| {
+                let values = (0..column.len()).map(|row| match column.get(row) {
+                    AnyValue::Utf8(value) => value.to_owned(),
+                    value => value.to_string(),
+                });
+                Some(column.name().to_owned())
+                    .into_iter()
+                    .chain(values)
+                    .collect()
+            })
+            .collect();
+        let widths: Vec<_> = cells
+            .iter()
+            .map(|column| column.iter().map(String::len).max().unwrap_or(0))
+            .collect();
+        let mut table = String::new();
+        for row in 0..=data_frame.height() {
+            let line: Vec<_> = cells
+                .iter()
+                .zip(&widths)
+                .map(|(column, width)| format!("{:<width$}", column[row]))
+                .collect();
+            table += line.join(" | ").trim_end();
+            table.push('\n');
+        }
+        self.print_message(&table);
+        Ok(())
+    }
+
+    fn plot(&mut self) -> VMResult<()> {
+        let quad = self.get_current_quad();
+        let col_1_name = String::from(self.get_value(quad.op_1.unwrap().raw())?);
+        let col_2_name = String::from(self.get_value(quad.op_2.unwrap().raw())?);
+        let temp = DataFrame::new(vec![
+            self.named_float_column(&col_1_name, "column_1")?,
+            self.named_float_column(&col_2_name, "column_2")?,
+        ])
+        .unwrap();
+        self.charts.push(Chart::new_plot(temp));
+        Ok(())
+    }
+
+    fn density(&mut self) -> VMResult<()> {
+        let quad = self.get_current_quad();
+        self.density = bool::from(&self.get_value(quad.op_1.unwrap().raw())?);
+        Ok(())
     }
 
     fn histogram(&mut self) -> VMResult<()> {
+        // Taken before anything can fail, so it never reaches another histogram
+        let density = std::mem::take(&mut self.density);
         let quad = self.get_current_quad();
-        let data_frame = self.get_dataframe()?;
-        let col_name = String::from(self.get_value(quad.op_1.unwrap())?);
-        let bins_value = self.get_value(quad.op_2.unwrap())?;
+        let col_name = String::from(self.get_value(quad.op_1.unwrap().raw())?);
+        let bins_value = self.get_value(quad.op_2.unwrap().raw())?;
         let bins = match bins_value {
-            VariableValue::Integer(a) if a <= 0 => Err("The amount of bins should be positive"),
+            VariableValue::Integer(a) if a <= 0 => {
+                Err("The amount of bins should be positive".to_owned())
+            }
             _ => Ok(usize::from(bins_value)),
         }?;
-        let temp = data_frame
-            .clone()
-            .lazy()
-            .select([col(&col_name).cast(DataType::Float64).alias("column")])
-            .collect()
-            .unwrap();
-        let app = App::new_histogram(temp, bins);
-        eframe::run_native(
-            "Raoul",
-            eframe::NativeOptions::default(),
-            Box::new(|_cc| Box::new(app)),
-        );
+        let temp = DataFrame::new(vec![self.named_float_column(&col_name, "column")?]).unwrap();
+        self.charts.push(Chart::new_histogram(temp, bins, density));
+        Ok(())
+    }
+
+    /// Opens a single window with the charts the program asked for, once it
+    /// has finished running
+    fn show_charts(&mut self) -> VMResult<()> {
+        if self.charts.is_empty() {
+            return Ok(());
+        }
+        let charts = std::mem::take(&mut self.charts);
+        gui::show(App::new(charts))
+    }
+
+    /// Runs the program like [`VM::run`], but returns everything it printed
+    /// instead of writing it to stdout. Charts are kept instead of shown
+    pub fn run_collect(&mut self) -> VMResult<String> {
+        let (output, res) = self.run_capture();
+        res.map(|()| output)
+    }
+
+    /// Runs the program without printing, returning what it printed even
+    /// when it stopped with an error
+    pub fn run_capture(&mut self) -> (String, VMResult<()>) {
+        self.output = Some(String::new());
+        let res = self.execute();
+        (self.output.take().unwrap_or_default(), res)
     }
 
     pub fn run(&mut self) -> VMResult<()> {
+        self.execute()?;
+        self.show_charts()
+    }
+
+    fn execute(&mut self) -> VMResult<()> {
         loop {
             let mut quad_pos = self.current_context().quad_pos;
+            self.steps += 1;
+            if let Some(max_steps) = self.max_steps.filter(|max| self.steps > *max) {
+                let reason = format!("Exceeded the limit of {max_steps} steps");
+                return Err(self.watchdog_error(&reason));
+            }
             if self.debug {
-                self.print_message(&format!("Quad - {quad_pos}\n"));
+                let quad = self.get_current_quad();
+                self.print_message(&format!("Quad - {quad_pos} {quad:?}\n"));
             }
             let quad = self.quad_list.get(quad_pos).unwrap();
-            match quad.operator {
+            let res = match quad.operator {
                 Operator::End => break,
                 Operator::Goto => {
-                    quad_pos = quad.res.unwrap() - 1;
+                    quad_pos = quad.res.unwrap().raw() - 1;
                     Ok(())
                 }
                 Operator::Assignment => self.process_assign(),
@@ -494,12 +1045,35 @@ impl VM {
                     Ok(())
                 }
                 Operator::Read => self.process_read(),
+                Operator::ReadBool => self.process_read_bool(),
+                Operator::Argv => self.process_argv(),
+                Operator::Random => self.process_random(),
+                Operator::RandomInt => self.process_random_int(),
                 Operator::Or => self.binary_operation(|a, b| Ok(a | b)),
                 Operator::And => self.binary_operation(|a, b| Ok(a & b)),
+                Operator::Implies => self.binary_operation(|a, b| Ok(!a | b)),
+                Operator::Iff => self.binary_operation(|a, b| {
+                    Ok(VariableValue::Bool(bool::from(a) == bool::from(b)))
+                }),
                 Operator::Sum => self.binary_operation(|a, b| a + b),
                 Operator::Minus => self.binary_operation(|a, b| a - b),
                 Operator::Times => self.binary_operation(|a, b| a * b),
                 Operator::Div => self.binary_operation(|a, b| a / b),
+                Operator::Contains => self.binary_operation(|a, b| {
+                    Ok(VariableValue::Bool(
+                        String::from(a).contains(&String::from(b)),
+                    ))
+                }),
+                Operator::StartsWith => self.binary_operation(|a, b| {
+                    Ok(VariableValue::Bool(
+                        String::from(a).starts_with(&String::from(b)),
+                    ))
+                }),
+                Operator::EndsWith => self.binary_operation(|a, b| {
+                    Ok(VariableValue::Bool(
+                        String::from(a).ends_with(&String::from(b)),
+                    ))
+                }),
                 Operator::Lt
                 | Operator::Lte
                 | Operator::Gt
@@ -507,11 +1081,23 @@ impl VM {
                 | Operator::Eq
                 | Operator::Ne => self.comparison(),
                 Operator::Not => self.unary_operation(|a| !a),
-                Operator::GotoF => {
-                    quad_pos = self.conditional_goto(false)?;
+                Operator::ToString => {
+                    self.unary_operation(|a| VariableValue::String(format!("{a:?}")))
+                }
+                Operator::GotoF => self.conditional_goto(false).map(|pos| quad_pos = pos),
+                Operator::Try => {
+                    self.process_try();
                     Ok(())
                 }
-                Operator::Inc => self.process_inc(),
+                Operator::EndTry => {
+                    self.handlers.pop();
+                    Ok(())
+                }
+                Operator::Assert => self.process_assert(),
+                Operator::Abort => self.process_abort(),
+                Operator::Append => self.process_append(),
+                Operator::Inc => self.process_step(VariableValue::increase),
+                Operator::Dec => self.process_step(VariableValue::decrease),
                 Operator::Era => self.process_era(),
                 Operator::GoSub => {
                     self.process_go_sub();
@@ -522,12 +1108,16 @@ impl VM {
                     continue;
                 }
                 Operator::Param => self.process_param(),
-                Operator::Return => {
-                    self.process_return()?;
-                    continue;
-                }
+                Operator::Return => match self.process_return() {
+                    Ok(()) => continue,
+                    error => error,
+                },
                 Operator::Ver => self.process_ver(),
+                Operator::VerCount => self.process_ver_count(),
                 Operator::ReadCSV => self.read_csv(),
+                Operator::ColumnsOf => self.columns_of(),
+                Operator::Split => self.split(),
+                Operator::SplitParts => self.split_parts(),
                 Operator::Rows | Operator::Columns => self.pure_df_operation(),
                 Operator::Average => self.unary_df_operation(|c| c.mean().unwrap_or(0.0)),
                 Operator::Std => {
@@ -540,12 +1130,27 @@ impl VM {
                 Operator::Min => self.unary_df_operation(min),
                 Operator::Max => self.unary_df_operation(max),
                 Operator::Range => self.unary_df_operation(|c| max(c) - min(c)),
+                Operator::NullCount => self.null_count(),
+                Operator::First => self.column_value(false),
+                Operator::Last => self.column_value(true),
+                Operator::ArgMin => self.column_arg(false),
+                Operator::ArgMax => self.column_arg(true),
                 Operator::Corr => self.correlation(),
                 Operator::Plot => self.plot(),
                 Operator::Histogram => self.histogram(),
-            }?;
+                Operator::Density => self.density(),
+                Operator::Describe => self.describe(),
+                Operator::CastColumn => self.cast_column(),
+                Operator::Show => self.show(),
+            };
+            if let Err(error) = res {
+                quad_pos = self.catch_error(error)? - 1;
+            }
             self.update_quad_pos(quad_pos + 1);
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests;
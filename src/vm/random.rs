@@ -0,0 +1,43 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+/// SplitMix64 generator, small and good enough for `random` and `random_int`
+#[derive(Clone, Debug)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Seeds the generator with the per-process keys of the standard library
+    pub fn from_entropy() -> Self {
+        Self::new(RandomState::new().build_hasher().finish())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform float in `[0, 1)`, using the top 53 bits as the mantissa
+    #[allow(clippy::cast_precision_loss)]
+    pub fn next_float(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Uniform integer in `[low, high]`, both ends included
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn next_int(&mut self, low: i64, high: i64) -> i64 {
+        let span = (i128::from(high) - i128::from(low) + 1) as u128;
+        let offset = (u128::from(self.next_u64()) * span) >> 64;
+        (i128::from(low) + offset as i128) as i64
+    }
+}
@@ -0,0 +1,115 @@
+use super::{
+    csv::unquote,
+    gui::{histogram_bins, Bin},
+    VM,
+};
+use crate::tests::compile;
+
+fn bin(start: f64, width: f64, count: usize) -> Bin {
+    Bin {
+        start,
+        width,
+        count,
+    }
+}
+
+#[test]
+fn histogram_bins_count_every_value() {
+    let values = [1.0, 2.0, 2.0, 3.0, 4.0, 10.0].map(Some);
+    let bins = histogram_bins(values.into_iter(), 3);
+    assert_eq!(
+        bins,
+        vec![bin(1.0, 3.0, 4), bin(4.0, 3.0, 1), bin(7.0, 3.0, 1)]
+    );
+}
+
+#[test]
+fn histogram_bins_keep_the_max_and_skip_missing_values() {
+    let values = [Some(0.0), None, Some(0.5), Some(1.0), Some(1.0), None];
+    let bins = histogram_bins(values.into_iter(), 2);
+    assert_eq!(bins, vec![bin(0.0, 0.5, 1), bin(0.5, 0.5, 3)]);
+}
+
+#[test]
+fn histogram_bins_of_clustered_values() {
+    let bins = histogram_bins([Some(5.0); 3].into_iter(), 2);
+    assert_eq!(bins, vec![bin(5.0, 1.0, 3), bin(6.0, 1.0, 0)]);
+    assert_eq!(histogram_bins([None, None].into_iter(), 2), vec![]);
+}
+
+#[test]
+fn failed_histogram_doesnt_keep_its_density() {
+    let program = "func main(): void {
+        data = read_csv(\"src/examples/data/small data.csv\");
+        try {
+            histogram(data, \"age\", 0, true);
+        } catch {
+            print(\"no bins\");
+        }
+    }";
+    let quad_manager = compile(program).unwrap();
+    let mut vm = VM::new(&quad_manager, false, false);
+    assert_eq!(vm.run_collect().unwrap(), "no bins\n");
+    assert!(!vm.density);
+}
+
+#[test]
+fn unquote_picks_a_delimiter_missing_from_the_fields() {
+    let source = b"name,city\n\"Doe, Jane\",Monterrey\n";
+    let (unquoted, delimiter) = unquote(source).unwrap();
+    assert_eq!(delimiter, b';');
+    assert_eq!(unquoted, b"name;city\nDoe, Jane;Monterrey\n");
+}
+
+#[test]
+fn unquote_unescapes_doubled_quotes() {
+    let source = b"id,quote\n1,\"say \"\"hi\"\"\"\n";
+    let (unquoted, delimiter) = unquote(source).unwrap();
+    assert_eq!(delimiter, b',');
+    assert_eq!(unquoted, b"id,quote\n1,say \"hi\"\n");
+}
+
+#[test]
+fn unquote_keeps_empty_fields() {
+    let (unquoted, _) = unquote(b"a,,\"\"\n,b").unwrap();
+    assert_eq!(unquoted, b"a,,\n,b\n");
+}
+
+#[test]
+fn unquote_rejects_unterminated_quotes() {
+    assert_eq!(unquote(b"a,\"b\n"), None);
+    assert_eq!(unquote(b"a,\"b"), None);
+}
+
+/// Benchmark of the `Float64` column cache, run with
+/// `cargo test --release -- --ignored --nocapture float_column_cache`
+#[test]
+#[ignore]
+fn float_column_cache_benchmark() {
+    let path = std::env::temp_dir().join("raoul-float-column-benchmark.csv");
+    let mut csv = String::from("count,ratio\n");
+    for i in 0..200_000 {
+        csv.push_str(&format!("{},{}\n", i % 97, f64::from(i % 89) / 7.0));
+    }
+    std::fs::write(&path, csv).unwrap();
+    let program = format!("func main(): void {{ data = read_csv({:?}); }}", path);
+    let quad_manager = compile(&program).unwrap();
+    let mut vm = VM::new(&quad_manager, false, false);
+    vm.run_collect().unwrap();
+    let mut time = |cached: bool| {
+        let start = std::time::Instant::now();
+        for _ in 0..400 {
+            for name in ["count", "ratio"] {
+                if !cached {
+                    vm.float_columns.clear();
+                }
+                assert_eq!(vm.float_column(name).unwrap().len(), 200_000);
+            }
+        }
+        start.elapsed()
+    };
+    let uncached = time(false);
+    let cached = time(true);
+    println!("800 float columns: {uncached:?} casting each time, {cached:?} cached");
+    std::fs::remove_file(path).unwrap();
+}